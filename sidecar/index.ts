@@ -6,10 +6,43 @@ interface RpcRequest {
   params?: Record<string, unknown>;
 }
 
+type RpcErrorCode =
+  | "invalid_params"
+  | "method_not_found"
+  | "not_initialized"
+  | "unauthenticated"
+  | "not_found"
+  | "rate_limited"
+  | "internal";
+
 interface RpcResponse {
   id: number;
   result?: unknown;
-  error?: string;
+  error?: { code: RpcErrorCode; message: string };
+}
+
+class RpcError extends Error {
+  constructor(
+    readonly code: RpcErrorCode,
+    message: string,
+  ) {
+    super(message);
+  }
+}
+
+// Map errors thrown by youtubei.js onto protocol error codes
+function toRpcError(error: unknown): RpcError {
+  if (error instanceof RpcError) {
+    return error;
+  }
+  const message = error instanceof Error ? error.message : String(error);
+  if (/\b429\b|rate limit|too many requests/i.test(message)) {
+    return new RpcError("rate_limited", message);
+  }
+  if (/\b404\b|not found|unavailable/i.test(message)) {
+    return new RpcError("not_found", message);
+  }
+  return new RpcError("internal", message);
 }
 
 interface LiveInfo {
@@ -20,6 +53,8 @@ interface LiveInfo {
   concurrentViewers: number;
   likeCount: number;
   isLive: boolean;
+  // Display-only metrics shown on the overlay; never used for scoring
+  extraMetrics?: Record<string, number>;
 }
 
 interface SuperChatEvent {
@@ -31,13 +66,19 @@ interface SuperChatEvent {
   timestamp: number;
 }
 
-interface PushEvent {
-  event: {
-    type: "superchat";
-    data: SuperChatEvent;
-  };
+interface MembershipEvent {
+  id: string;
+  author: string;
+  kind: "new" | "milestone" | "gift";
+  // Gifted memberships count every recipient; everything else is 1
+  count: number;
+  timestamp: number;
 }
 
+type PushEvent =
+  | { event: { type: "superchat"; data: SuperChatEvent } }
+  | { event: { type: "membership"; data: MembershipEvent } };
+
 let youtube: Innertube | null = null;
 let liveChatInstance: YT.LiveChat | null = null;
 let storedCookies: string | null = null;
@@ -61,9 +102,15 @@ function setCookies(cookies: string): void {
   console.error("Cookies stored for authentication");
 }
 
+// The client keeps its session until the next init
+function clearCookies(): void {
+  storedCookies = null;
+  console.error("Cookies cleared");
+}
+
 async function getLiveInfo(videoId: string): Promise<LiveInfo> {
   if (!youtube) {
-    throw new Error("YouTube client not initialized");
+    throw new RpcError("not_initialized", "YouTube client not initialized");
   }
 
   const info = await youtube.getInfo(videoId);
@@ -158,14 +205,36 @@ function parseSuperchatAmount(purchaseAmount: string): {
   };
 }
 
+function textOf(value: unknown): string {
+  if (typeof value === "string") {
+    return value;
+  }
+  return (value as { text?: string } | undefined)?.text || "";
+}
+
+// "Gifted 5 Channel memberships" / "5 件のメンバーシップ ギフトを贈りました"
+function parseGiftCount(text: string): number {
+  const match = text.replace(/,/g, "").match(/\d+/);
+  return match ? Number.parseInt(match[0], 10) : 1;
+}
+
+function emitMembership(data: MembershipEvent): void {
+  emitPushEvent({
+    event: {
+      type: "membership",
+      data,
+    },
+  }).catch((e) => console.error("Failed to emit membership event:", e));
+}
+
 async function emitPushEvent(event: PushEvent): Promise<void> {
   const output = `${JSON.stringify(event)}\n`;
   await Bun.write(Bun.stdout, encoder.encode(output));
 }
 
-async function startLiveChat(videoId: string): Promise<void> {
+async function startLiveChat(videoId: string, chatId?: string): Promise<void> {
   if (!youtube) {
-    throw new Error("YouTube client not initialized");
+    throw new RpcError("not_initialized", "YouTube client not initialized");
   }
 
   if (liveChatInstance) {
@@ -173,8 +242,17 @@ async function startLiveChat(videoId: string): Promise<void> {
     liveChatInstance = null;
   }
 
-  const info = await youtube.getInfo(videoId);
-  const liveChat = info.getLiveChat();
+  // Chat may be keyed on a different ID than the watch video
+  const info = await youtube.getInfo(chatId || videoId);
+  let liveChat: YT.LiveChat;
+  try {
+    liveChat = info.getLiveChat();
+  } catch (e) {
+    throw new RpcError(
+      "not_found",
+      `Chat not available for ${chatId || videoId}: ${e instanceof Error ? e.message : String(e)}`,
+    );
+  }
 
   liveChat.on("chat-update", (action) => {
     if (action.is(YTNodes.AddChatItemAction)) {
@@ -205,6 +283,27 @@ async function startLiveChat(videoId: string): Promise<void> {
             data: superchatEvent,
           },
         }).catch((e) => console.error("Failed to emit superchat event:", e));
+      } else if (item?.is(YTNodes.LiveChatMembershipItem)) {
+        // Milestone messages carry a "Member for N months" header; new members do not
+        const milestone = textOf(item.header_primary_text) !== "";
+        emitMembership({
+          id: item.id || `member-${Date.now()}`,
+          author: textOf(item.author?.name) || "Unknown",
+          kind: milestone ? "milestone" : "new",
+          count: 1,
+          timestamp: Date.now(),
+        });
+      } else if (item?.is(YTNodes.LiveChatSponsorshipsGiftPurchaseAnnouncement)) {
+        const header = item.header as
+          | { author_name?: unknown; primary_text?: unknown }
+          | undefined;
+        emitMembership({
+          id: item.id || `gift-${Date.now()}`,
+          author: textOf(header?.author_name) || "Unknown",
+          kind: "gift",
+          count: parseGiftCount(textOf(header?.primary_text)),
+          timestamp: Date.now(),
+        });
       }
     }
   });
@@ -226,7 +325,7 @@ function stopLiveChat(): void {
 
 async function getChannelSubscriberCount(channelId: string): Promise<number> {
   if (!youtube) {
-    throw new Error("YouTube client not initialized");
+    throw new RpcError("not_initialized", "YouTube client not initialized");
   }
 
   try {
@@ -273,11 +372,11 @@ async function getChannelSubscriberCount(channelId: string): Promise<number> {
 
 async function getExactSubscriberCount(): Promise<number> {
   if (!youtube) {
-    throw new Error("YouTube client not initialized");
+    throw new RpcError("not_initialized", "YouTube client not initialized");
   }
 
   if (!storedCookies) {
-    throw new Error("Authentication required for exact subscriber count");
+    throw new RpcError("unauthenticated", "Authentication required for exact subscriber count");
   }
 
   try {
@@ -352,17 +451,22 @@ async function handleRequest(request: RpcRequest): Promise<RpcResponse> {
       case "setCookies": {
         const cookies = request.params?.cookies as string;
         if (!cookies) {
-          throw new Error("cookies is required");
+          throw new RpcError("invalid_params", "cookies is required");
         }
         setCookies(cookies);
         result = { success: true };
         break;
       }
 
+      case "clearCookies":
+        clearCookies();
+        result = { success: true };
+        break;
+
       case "getLiveInfo": {
         const videoId = request.params?.videoId as string;
         if (!videoId) {
-          throw new Error("videoId is required");
+          throw new RpcError("invalid_params", "videoId is required");
         }
         result = await getLiveInfo(videoId);
         break;
@@ -371,7 +475,7 @@ async function handleRequest(request: RpcRequest): Promise<RpcResponse> {
       case "getSubscriberCount": {
         const channelId = request.params?.channelId as string;
         if (!channelId) {
-          throw new Error("channelId is required");
+          throw new RpcError("invalid_params", "channelId is required");
         }
         result = { count: await getChannelSubscriberCount(channelId) };
         break;
@@ -384,9 +488,10 @@ async function handleRequest(request: RpcRequest): Promise<RpcResponse> {
       case "startLiveChat": {
         const videoId = request.params?.videoId as string;
         if (!videoId) {
-          throw new Error("videoId is required");
+          throw new RpcError("invalid_params", "videoId is required");
         }
-        await startLiveChat(videoId);
+        const chatId = (request.params?.chatId as string | null) ?? undefined;
+        await startLiveChat(videoId, chatId);
         result = { success: true };
         break;
       }
@@ -401,15 +506,13 @@ async function handleRequest(request: RpcRequest): Promise<RpcResponse> {
         break;
 
       default:
-        throw new Error(`Unknown method: ${request.method}`);
+        throw new RpcError("method_not_found", `Unknown method: ${request.method}`);
     }
 
     return { id: request.id, result };
   } catch (error) {
-    return {
-      id: request.id,
-      error: error instanceof Error ? error.message : String(error),
-    };
+    const { code, message } = toRpcError(error);
+    return { id: request.id, error: { code, message } };
   }
 }
 
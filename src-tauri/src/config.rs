@@ -1,15 +1,550 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::sync::LazyLock;
 
-/// ポーリング間隔（秒）
+use crate::points::RawMetrics;
+
+/// ポーリング間隔の既定値（秒）
 pub const POLLING_INTERVAL_SECONDS: u64 = 5;
 
+/// 同時接続者数ボーナスの条件（この人数を超えたら1回だけ付与）
+pub const CONCURRENT_BONUS_THRESHOLD: i64 = 50;
+
+/// 同時接続者数ボーナスの金額
+pub const CONCURRENT_BONUS_POINTS: i64 = 1000;
+
+/// この回数分のポーリング間隔を超えて更新がなければデータを古いとみなす
+pub const STALE_AFTER_POLLS: u64 = 3;
+
+/// 開始時の登録者数が後の値のこの割合未満なら不正な基準値とみなす
+pub const BASELINE_PLAUSIBLE_RATIO: f64 = 0.5;
+
+/// 同時接続者数が直前の値のこの割合未満に落ちたら取得失敗の疑いがあるとみなす
+pub const VIEWER_DIP_RATIO: f64 = 0.1;
+
+/// 直前の同時接続者数がこの人数未満なら急落の判定をしない
+pub const VIEWER_DIP_MIN_PREVIOUS: i64 = 10;
+
+/// ハートビート送信間隔（秒）
+pub const HEARTBEAT_INTERVAL_SECONDS: u64 = 3;
+
+/// 保持する直近のスーパーチャット件数
+pub const RECENT_SUPERCHATS_LIMIT: usize = 20;
+
+/// スーパーチャットの時刻と受信時刻のずれがこれを超えたら受信時刻で置き換える（秒）
+pub const CLOCK_SKEW_THRESHOLD_SECONDS: i64 = 300;
+
+/// 視聴者用ウィンドウの標準サイズ（論理ピクセル）
+pub const VIEWER_WINDOW_WIDTH: f64 = 800.0;
+pub const VIEWER_WINDOW_HEIGHT: f64 = 350.0;
+
+/// 保持する配信履歴の件数（古いものから削除）
+pub const SESSION_HISTORY_LIMIT: usize = 50;
+
+/// 保持する指標の推移の件数（5 秒間隔で約 12 時間分、古いものから削除）
+pub const METRIC_HISTORY_LIMIT: usize = 8640;
+
+/// サイドカーが予期せず終了したときに再起動を試みる回数
+pub const SIDECAR_RESTART_MAX_ATTEMPTS: u32 = 5;
+
+/// 再起動の最初の待ち時間（秒）。失敗するたびに倍にする
+pub const SIDECAR_RESTART_BASE_DELAY_SECONDS: u64 = 1;
+
+/// 監視中にサイドカーの応答を確認する間隔（秒）
+pub const SIDECAR_PING_INTERVAL_SECONDS: u64 = 10;
+
+/// `ping` の応答を待つ最大時間（秒）。過ぎたら応答なしとみなす
+pub const SIDECAR_PING_TIMEOUT_SECONDS: u64 = 3;
+
+/// オーバーレイのカウントアップにかける時間の上限（ミリ秒）
+pub const MAX_ANIMATION_DURATION_MS: u64 = 10_000;
+
+/// メンバーシップ加入のレートの既定値（points_config.toml と同じ値）
+const DEFAULT_MEMBERSHIP_RATE: f64 = 50.0;
+
 /// ポイント計算設定（コンパイル時に埋め込み）
 const POINTS_CONFIG_TOML: &str = include_str!("points_config.toml");
 
 pub static POINTS_CONFIG: LazyLock<PointsConfig> =
     LazyLock::new(|| toml::from_str(POINTS_CONFIG_TOML).expect("Invalid points_config.toml"));
 
+/// アプリのデータ保存先ディレクトリ名（tauri.conf.json の identifier と同じ）
+pub const APP_DIR_NAME: &str = "com.ro.yt-point";
+
+/// 設定ファイル名
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// 標準のサイドカー名（tauri.conf.json の externalBin）
+pub const DEFAULT_SIDECAR_NAME: &str = "youtube-sidecar";
+
+/// サイドカー名を上書きする環境変数
+const SIDECAR_NAME_ENV: &str = "YT_POINT_SIDECAR";
+
+/// 実行時設定
+///
+/// 起動時に `config.toml` から読み込まれ、`AppState.config` に保持される。
+/// `AppState.config` が実際に使われている値（実効設定）で、ディスク上の
+/// `config.toml` と一致するとは限らない。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// ポイント計算設定
+    pub points: PointsConfig,
+    /// 名前付きのポイント計算設定（プロファイル）
+    pub profiles: BTreeMap<String, PointsConfig>,
+    /// 現在有効なプロファイル名
+    pub active_profile: Option<String>,
+    /// OBS 用 Web サーバー設定
+    pub server: ServerConfig,
+    /// OBS 用オーバーレイの表示設定
+    pub overlay: OverlayConfig,
+    /// サイドカー設定
+    pub sidecar: SidecarConfig,
+    /// ポーリング間隔の設定
+    pub polling: PollingConfig,
+    /// 取得した指標の扱いに関する設定
+    pub metrics: MetricsConfig,
+    /// 配信セッションの設定
+    pub session: SessionConfig,
+    /// スーパーチャットの通貨換算の設定
+    pub currency: CurrencyConfig,
+}
+
+impl Config {
+    /// 設定ファイルのパス
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_DIR_NAME).join(CONFIG_FILE_NAME))
+    }
+
+    /// ディスク上の設定を読み込む
+    pub fn load() -> Result<Self, String> {
+        let path = Self::path().ok_or("Config directory not available")?;
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let config: Self =
+            toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+        config
+            .validate()
+            .map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    /// ポイント計算設定・すべてのプロファイルのレート・通貨の換算レート・RPC のタイムアウト・
+    /// ポーリング間隔を検証する
+    pub fn validate(&self) -> Result<(), String> {
+        self.points.validate()?;
+        self.currency.validate()?;
+        self.polling.validate()?;
+        if self.sidecar.rpc_timeout_seconds == 0 {
+            return Err("sidecar.rpc_timeout_seconds must be greater than 0".to_string());
+        }
+        for (name, profile) in &self.profiles {
+            profile
+                .validate()
+                .map_err(|e| format!("profile {}: {}", name, e))?;
+        }
+        Ok(())
+    }
+
+    /// 設定をディスクに保存する
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("Config directory not available")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// プロファイルを切り替え、そのポイント計算設定を有効にする（保存はしない）
+    pub fn switch_profile(&mut self, name: &str) -> Result<(), String> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| format!("Unknown profile: {}", name))?;
+        self.points = profile.clone();
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// 操作系エンドポイントの認証トークン
+    ///
+    /// `Authorization: Bearer <token>` ヘッダーまたは `?token=<token>` で渡す。
+    /// 未指定時は認証しないが、状態を変える操作はブラウザからなら OBS 用ページ自身からのみ受け付ける。
+    pub auth_token: Option<String>,
+    /// Web クライアントへ送信する最短間隔（ミリ秒、0 で無効）
+    ///
+    /// 間隔内に届いた更新はまとめ、最後の状態だけを間隔の終わりに送る。
+    pub min_broadcast_interval_ms: u64,
+    /// 優先して使うポート（使用中または未指定なら 1430〜1460 の空きポートを探す）
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SidecarConfig {
+    /// 代替サイドカーのバイナリ名（未指定時は youtube-sidecar）
+    ///
+    /// 環境変数 `YT_POINT_SIDECAR` が設定されていればそちらを優先する。
+    /// 代替バイナリはアプリ本体と同じディレクトリに配置し、`SidecarManager` に
+    /// 記載の RPC 仕様を満たす必要がある。
+    pub name: Option<String>,
+    /// RPC の応答を待つ最大時間（秒）。`ping` は別の短い時間を使う
+    pub rpc_timeout_seconds: u64,
+}
+
+impl Default for SidecarConfig {
+    fn default() -> Self {
+        Self {
+            name: None,
+            rpc_timeout_seconds: 30,
+        }
+    }
+}
+
+impl SidecarConfig {
+    /// 実際に起動するサイドカー名（不正な名前の場合は標準名にフォールバック）
+    pub fn binary_name(&self) -> String {
+        let requested = std::env::var(SIDECAR_NAME_ENV)
+            .ok()
+            .or_else(|| self.name.clone());
+        match requested {
+            Some(name) if is_valid_sidecar_name(&name) => name,
+            Some(name) => {
+                eprintln!(
+                    "Invalid sidecar name {:?}, falling back to {}",
+                    name, DEFAULT_SIDECAR_NAME
+                );
+                DEFAULT_SIDECAR_NAME.to_string()
+            }
+            None => DEFAULT_SIDECAR_NAME.to_string(),
+        }
+    }
+}
+
+/// ポーリング間隔の設定
+///
+/// 同接数・高評価数が変わらない間は間隔を倍にしていき（`max_interval_seconds` まで）、
+/// 変化したら `min_interval_seconds` に戻す。両者が同じなら固定間隔でポーリングする。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PollingConfig {
+    /// 最短のポーリング間隔（秒）
+    pub min_interval_seconds: u64,
+    /// 最長のポーリング間隔（秒）
+    pub max_interval_seconds: u64,
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        Self {
+            min_interval_seconds: POLLING_INTERVAL_SECONDS,
+            max_interval_seconds: POLLING_INTERVAL_SECONDS,
+        }
+    }
+}
+
+impl PollingConfig {
+    /// 間隔の範囲を検証する
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_interval_seconds == 0 {
+            return Err("polling.min_interval_seconds must be greater than 0".to_string());
+        }
+        if self.max_interval_seconds < self.min_interval_seconds {
+            return Err(
+                "polling.max_interval_seconds must not be less than min_interval_seconds"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// 前回の間隔と指標が変化したかどうかから次の間隔（秒）を決める
+    pub fn next_interval_seconds(&self, current: u64, changed: bool) -> u64 {
+        if changed {
+            self.min_interval_seconds
+        } else {
+            current
+                .saturating_mul(2)
+                .clamp(self.min_interval_seconds, self.max_interval_seconds)
+        }
+    }
+}
+
+fn is_valid_sidecar_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// オーバーレイに内訳を表示するソース（ポイント計算には影響しない）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceVisibility {
+    pub superchat: bool,
+    pub viewers: bool,
+    pub likes: bool,
+    pub subscribers: bool,
+}
+
+impl Default for SourceVisibility {
+    fn default() -> Self {
+        Self {
+            superchat: true,
+            viewers: true,
+            likes: true,
+            subscribers: true,
+        }
+    }
+}
+
+impl SourceVisibility {
+    /// ソース名（`superchat` / `viewers` / `likes` / `subscribers`）の表示を切り替える
+    pub fn set(&mut self, source: &str, visible: bool) -> Result<(), String> {
+        let flag = match source {
+            "superchat" => &mut self.superchat,
+            "viewers" => &mut self.viewers,
+            "likes" => &mut self.likes,
+            "subscribers" => &mut self.subscribers,
+            _ => return Err(format!("Unknown source: {}", source)),
+        };
+        *flag = visible;
+        Ok(())
+    }
+}
+
+/// 進捗バーが追う指標
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressMetric {
+    /// 合計ポイント（`goal_counts_manual` を反映）
+    #[default]
+    Total,
+    /// 同時接続者数
+    Viewers,
+    /// 新規登録者数
+    Subscribers,
+    /// スーパーチャット累計金額（円）
+    Superchat,
+}
+
+impl ProgressMetric {
+    /// 進捗に使う値。`goal_points` は合計ポイントのうち目標の進捗に数える分
+    pub fn value(self, goal_points: i64, metrics: &RawMetrics) -> i64 {
+        match self {
+            Self::Total => goal_points,
+            Self::Viewers => metrics.concurrent_viewers,
+            Self::Subscribers => metrics.current_subscribers - metrics.initial_subscribers,
+            Self::Superchat => metrics.superchat_amount,
+        }
+    }
+}
+
+impl std::str::FromStr for ProgressMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "total" => Ok(Self::Total),
+            "viewers" => Ok(Self::Viewers),
+            "subscribers" => Ok(Self::Subscribers),
+            "superchat" => Ok(Self::Superchat),
+            _ => Err(format!("Unknown progress metric: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayConfig {
+    /// オーバーレイ上部のタイトル
+    pub title: String,
+    /// ポイント増加時のポップアップ文言（`{amount}` が増加量に置き換わる）
+    pub popup_template: String,
+    /// フラッシュ演出の最短間隔（ミリ秒）。この間隔内の演出は 1 回にまとめる
+    pub effect_cooldown_ms: u64,
+    /// 合計ポイントのカウントアップにかける時間（ミリ秒、0 で即座に切り替え）
+    pub animation_duration_ms: u64,
+    /// 同時に表示するポップアップの最大数（超えた分は古いものから消す）
+    pub max_popups: usize,
+    /// この増加量以上でフラッシュ演出を行う（未満はポップアップのみ）
+    pub flash_threshold: i64,
+    /// この増加量以上で大きなお祝い演出を行う（未指定時は無効）
+    pub celebration_threshold: Option<i64>,
+    /// 手動ポイント（埼玉ボーナス）とライバー訪問を目標の進捗に含めるか
+    ///
+    /// `false` にすると進捗バーは配信の指標から得たポイントのみで計算される。
+    /// 合計ポイントには常に含まれる。
+    pub goal_counts_manual: bool,
+    /// 進捗バーの目標（`progress_metric` の単位）
+    pub goal: i64,
+    /// 段階的な目標（昇順）。空の場合は `goal` のみを目標にする
+    ///
+    /// 最初の未達成の目標が進捗バーの目標になり、達成すると次の目標に進む。
+    pub goals: Vec<i64>,
+    /// 進捗バーが追う指標
+    pub progress_metric: ProgressMetric,
+    /// フラッシュとお祝いの演出を行うか（`false` でもポップアップは表示する）
+    pub superchat_effects: bool,
+    /// 内訳のタイルを表示するソース
+    pub visible: SourceVisibility,
+    /// 目標達成までの見込み時間をオーバーレイに表示する（`progress_metric` が合計の場合のみ）
+    pub show_time_to_goal: bool,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            title: "LIVE POINTS".to_string(),
+            popup_template: "+{amount}".to_string(),
+            effect_cooldown_ms: 1000,
+            animation_duration_ms: 500,
+            max_popups: 8,
+            flash_threshold: 10,
+            celebration_threshold: None,
+            goal_counts_manual: true,
+            goal: 1000,
+            goals: Vec::new(),
+            progress_metric: ProgressMetric::Total,
+            superchat_effects: true,
+            visible: SourceVisibility::default(),
+            show_time_to_goal: false,
+        }
+    }
+}
+
+impl OverlayConfig {
+    /// 目標の一覧（`goals` が空なら `goal` のみ）
+    pub fn goal_list(&self) -> Vec<i64> {
+        if self.goals.is_empty() {
+            vec![self.goal]
+        } else {
+            self.goals.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// 配信終了を検知してから自動でポイントをリセットするまでの秒数（未指定時はリセットしない）
+    pub auto_reset_after_end_seconds: Option<u64>,
+    /// ポイント更新ごとの履歴の最大件数（超えたら古いものから削除）
+    pub history_limit: usize,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            auto_reset_after_end_seconds: None,
+            history_limit: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurrencyConfig {
+    /// 基準通貨（スーパーチャットの累計金額はこの通貨で数える）
+    pub base: String,
+    /// 通貨コードごとの基準通貨への換算レート（1 単位あたりの基準通貨の額）
+    pub rates: BTreeMap<String, f64>,
+    /// `rates` に無い通貨に使う換算レート
+    pub fallback_rate: f64,
+}
+
+impl CurrencyConfig {
+    /// 通貨コードに対応する換算レート。基準通貨は常に 1、未知の通貨は `None`
+    pub fn rate(&self, currency: &str) -> Option<f64> {
+        let currency = currency.to_ascii_uppercase();
+        if currency == self.base.to_ascii_uppercase() {
+            return Some(1.0);
+        }
+        self.rates
+            .iter()
+            .find(|(code, _)| code.to_ascii_uppercase() == currency)
+            .map(|(_, rate)| *rate)
+    }
+
+    /// 換算レートがすべて正の有限値であることを確認する
+    pub fn validate(&self) -> Result<(), String> {
+        let rates = self
+            .rates
+            .iter()
+            .map(|(code, rate)| (format!("currency.rates.{}", code), *rate))
+            .chain([("currency.fallback_rate".to_string(), self.fallback_rate)]);
+        for (name, rate) in rates {
+            if !rate.is_finite() || rate <= 0.0 {
+                return Err(format!("{} must be greater than 0 (got {})", name, rate));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for CurrencyConfig {
+    fn default() -> Self {
+        // YouTube のスーパーチャットでよく使われる通貨のおおよその円換算
+        let rates = [
+            ("USD", 150.0),
+            ("EUR", 160.0),
+            ("GBP", 190.0),
+            ("CAD", 110.0),
+            ("AUD", 100.0),
+            ("HKD", 19.0),
+            ("TWD", 4.7),
+            ("KRW", 0.11),
+            ("INR", 1.8),
+        ];
+        Self {
+            base: "JPY".to_string(),
+            rates: rates
+                .into_iter()
+                .map(|(code, rate)| (code.to_string(), rate))
+                .collect(),
+            fallback_rate: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// 配信開始時の登録者数が不正な場合に一度だけ基準値を補正する
+    ///
+    /// 開始時の値が 0 以下、または後の値の半分未満だった場合、開始時の取得が
+    /// 失敗していたとみなし、最初に得られた正の値を新しい基準値にする。
+    /// 補正はセッション中 1 回のみ行う。
+    pub rebaseline_subscribers: bool,
+    /// 高評価数が減ってもポイントを減らさない
+    ///
+    /// 有効な場合、セッション中の最大の高評価数を保持してポイントを計算する。
+    pub clamp_negative_likes: bool,
+    /// 同時接続者数の一時的な急落（0 など）を 1 回だけ無視する
+    ///
+    /// 直前の値から急に落ちた値は取得失敗とみなして直前の値を保持し、
+    /// 次の取得でも低いままならその値を採用する。
+    pub suppress_viewer_dips: bool,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            rebaseline_subscribers: true,
+            clamp_negative_likes: false,
+            suppress_viewer_dips: true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PointsConfig {
     /// スーパーチャットのレート（円 / ポイント）
@@ -24,4 +559,196 @@ pub struct PointsConfig {
     pub manual_rate: f64,
     /// ライバー訪問のレート（1人につき200円）
     pub visitor_rate: f64,
+    /// メンバーシップ加入のレート（1人につき50円。ギフトは贈られた人数分）
+    #[serde(default = "default_membership_rate")]
+    pub membership_rate: f64,
+    /// 合計ポイントの上限（`None` なら上限なし）
+    #[serde(default)]
+    pub total_cap: Option<i64>,
+    /// スーパーチャットのポイントの上限（`None` なら上限なし）
+    #[serde(default)]
+    pub superchat_cap: Option<i64>,
+    /// 同時接続者数のポイントの上限（同接ボーナスにも適用）
+    #[serde(default)]
+    pub concurrent_cap: Option<i64>,
+    /// 高評価のポイントの上限
+    #[serde(default)]
+    pub like_cap: Option<i64>,
+    /// 新規登録者のポイントの上限
+    #[serde(default)]
+    pub subscriber_cap: Option<i64>,
+    /// スーパーチャット累計金額のマイルストーン（達成ごとにセッション中 1 回だけボーナス）
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,
+    /// スーパーチャットをポイントに数えるか（無効でも指標は取得・表示する）
+    #[serde(default = "default_enabled")]
+    pub superchat_enabled: bool,
+    /// 同時接続者数（同接ボーナスを含む）をポイントに数えるか
+    #[serde(default = "default_enabled")]
+    pub concurrent_enabled: bool,
+    /// 高評価をポイントに数えるか
+    #[serde(default = "default_enabled")]
+    pub like_enabled: bool,
+    /// 新規登録者をポイントに数えるか
+    #[serde(default = "default_enabled")]
+    pub subscriber_enabled: bool,
+}
+
+/// スーパーチャット累計金額のマイルストーン
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Milestone {
+    /// 達成とみなす累計金額（円）
+    pub threshold_yen: i64,
+    /// 達成時に加算するポイント
+    pub bonus_points: i64,
+}
+
+impl PointsConfig {
+    /// 割り算に使うレートがすべて正の有限値であることを確認する
+    ///
+    /// 0 は無限大や NaN に、負の値はポイントの符号の反転につながる。
+    /// 既定の `like_rate` (0.1) のように 1 未満のレートは正しい設定として受け付ける。
+    /// 項目ごとの上限が負でないことも確認する。
+    pub fn validate(&self) -> Result<(), String> {
+        let divisors = [
+            ("superchat_rate", self.superchat_rate),
+            ("concurrent_rate", self.concurrent_rate),
+            ("like_rate", self.like_rate),
+            ("subscriber_rate", self.subscriber_rate),
+        ];
+        for (name, rate) in divisors {
+            if !rate.is_finite() || rate <= 0.0 {
+                return Err(format!("{} must be greater than 0 (got {})", name, rate));
+            }
+        }
+        let caps = [
+            ("superchat_cap", self.superchat_cap),
+            ("concurrent_cap", self.concurrent_cap),
+            ("like_cap", self.like_cap),
+            ("subscriber_cap", self.subscriber_cap),
+        ];
+        for (name, cap) in caps {
+            if let Some(cap) = cap.filter(|cap| *cap < 0) {
+                return Err(format!("{} must not be negative (got {})", name, cap));
+            }
+        }
+        for milestone in &self.milestones {
+            if milestone.threshold_yen <= 0 {
+                return Err(format!(
+                    "milestone threshold_yen must be greater than 0 (got {})",
+                    milestone.threshold_yen
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// スーパーチャット累計金額 `superchat_amount` で達成済みのマイルストーン
+    pub fn milestones_reached_at(&self, superchat_amount: i64) -> impl Iterator<Item = &Milestone> {
+        self.milestones
+            .iter()
+            .filter(move |milestone| superchat_amount >= milestone.threshold_yen)
+    }
+
+    /// `superchat_amount` で達成したマイルストーンのうち、`reached` にまだ無いもの
+    pub fn new_milestones(&self, superchat_amount: i64, reached: &[Milestone]) -> Vec<Milestone> {
+        self.milestones_reached_at(superchat_amount)
+            .filter(|milestone| !reached.contains(milestone))
+            .cloned()
+            .collect()
+    }
+}
+
+/// `membership_rate` が無い古い設定ファイル向けの既定値
+fn default_membership_rate() -> f64 {
+    DEFAULT_MEMBERSHIP_RATE
+}
+
+/// 項目ごとの有効フラグが無い古い設定ファイル向けの既定値
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for PointsConfig {
+    fn default() -> Self {
+        POINTS_CONFIG.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_like_rate(rate: f64) -> PointsConfig {
+        PointsConfig {
+            like_rate: rate,
+            ..PointsConfig::default()
+        }
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(Config::default().validate().is_ok());
+        assert!(POINTS_CONFIG.validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_zero_and_negative_rates() {
+        assert!(with_like_rate(0.0).validate().is_err());
+        assert!(with_like_rate(-1.0).validate().is_err());
+    }
+
+    #[test]
+    fn rejects_non_finite_rates() {
+        assert!(with_like_rate(f64::NAN).validate().is_err());
+        assert!(with_like_rate(f64::INFINITY).validate().is_err());
+    }
+
+    fn with_milestones() -> PointsConfig {
+        PointsConfig {
+            milestones: vec![
+                Milestone {
+                    threshold_yen: 1000,
+                    bonus_points: 10,
+                },
+                Milestone {
+                    threshold_yen: 5000,
+                    bonus_points: 50,
+                },
+                Milestone {
+                    threshold_yen: 10000,
+                    bonus_points: 100,
+                },
+            ],
+            ..PointsConfig::default()
+        }
+    }
+
+    #[test]
+    fn one_superchat_can_reach_several_milestones() {
+        let config = with_milestones();
+        let reached = config.new_milestones(6000, &[]);
+        let thresholds: Vec<i64> = reached.iter().map(|m| m.threshold_yen).collect();
+        assert_eq!(thresholds, [1000, 5000]);
+    }
+
+    #[test]
+    fn reached_milestones_are_not_returned_again() {
+        let config = with_milestones();
+        let reached = config.new_milestones(6000, &[]);
+        assert!(config.new_milestones(6000, &reached).is_empty());
+
+        let next = config.new_milestones(12000, &reached);
+        assert_eq!(next, [config.milestones[2].clone()]);
+    }
+
+    #[test]
+    fn accepts_fractional_rates() {
+        assert!(with_like_rate(0.1).validate().is_ok());
+        let config = PointsConfig {
+            subscriber_rate: 0.02,
+            ..PointsConfig::default()
+        };
+        assert!(config.validate().is_ok());
+    }
 }
@@ -0,0 +1,41 @@
+/// YouTube の認証に必要な Cookie
+pub const REQUIRED_AUTH_COOKIES: [&str; 2] = ["SAPISID", "__Secure-3PSID"];
+
+/// Netscape 形式の Cookie ファイルから YouTube の Cookie を `(name, value)` で取り出す
+///
+/// 各行は `domain, include_subdomains, path, secure, expiry, name, value` のタブ区切り。
+/// `#` で始まる行はコメントとして扱う（`#HttpOnly_` で始まる行は除く）。
+pub fn parse_netscape_cookies(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 7 || !fields[0].contains("youtube.com") {
+                return None;
+            }
+            Some((fields[5].to_string(), fields[6].to_string()))
+        })
+        .collect()
+}
+
+/// 必須の認証 Cookie のうち含まれていないもの
+pub fn missing_auth_cookies(cookies: &[(String, String)]) -> Vec<&'static str> {
+    REQUIRED_AUTH_COOKIES
+        .into_iter()
+        .filter(|required| !cookies.iter().any(|(name, _)| name == required))
+        .collect()
+}
+
+/// `name=value; ...` 形式の Cookie 文字列にする
+pub fn to_cookie_header(cookies: &[(String, String)]) -> String {
+    cookies
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
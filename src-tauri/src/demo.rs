@@ -0,0 +1,162 @@
+/// デモの 1 ステップの間隔（秒）
+pub const DEMO_TICK_SECONDS: u64 = 2;
+
+/// デモで送られるスーパーチャット
+pub struct DemoSuperchat {
+    /// 送られるステップ
+    pub tick: u32,
+    pub amount: i64,
+    pub author: &'static str,
+    pub message: &'static str,
+}
+
+/// 台本付きのデモ配信
+pub struct DemoScenario {
+    pub name: &'static str,
+    /// ステップ数（`DEMO_TICK_SECONDS` 秒ごとに 1 ステップ）
+    pub ticks: u32,
+    /// 開始時の同時接続者数
+    pub viewers_start: i64,
+    /// 同時接続者数の最大値（前半で到達し、以降はこの付近で推移する）
+    pub viewers_peak: i64,
+    /// 1 ステップあたりの高評価の増加数
+    pub likes_per_tick: i64,
+    /// 登録者が 1 人増えるまでのステップ数
+    pub subscriber_every: u32,
+    /// 開始時の登録者数
+    pub initial_subscribers: i64,
+    pub superchats: &'static [DemoSuperchat],
+}
+
+/// 同時接続者数の揺らぎ（毎回同じ映像になるよう固定のパターン）
+const VIEWER_WOBBLE: [i64; 5] = [0, 2, -1, 3, -2];
+
+pub const DEMO_SCENARIOS: &[DemoScenario] = &[
+    DemoScenario {
+        name: "quiet",
+        ticks: 90,
+        viewers_start: 8,
+        viewers_peak: 55,
+        likes_per_tick: 1,
+        subscriber_every: 15,
+        initial_subscribers: 1200,
+        superchats: &[
+            DemoSuperchat {
+                tick: 20,
+                amount: 200,
+                author: "もぐもぐ",
+                message: "こんばんは！",
+            },
+            DemoSuperchat {
+                tick: 55,
+                amount: 500,
+                author: "ねこやしき",
+                message: "今日も楽しい",
+            },
+            DemoSuperchat {
+                tick: 80,
+                amount: 1000,
+                author: "さいたま在住",
+                message: "応援してます",
+            },
+        ],
+    },
+    DemoScenario {
+        name: "hype",
+        ticks: 120,
+        viewers_start: 30,
+        viewers_peak: 420,
+        likes_per_tick: 6,
+        subscriber_every: 3,
+        initial_subscribers: 8500,
+        superchats: &[
+            DemoSuperchat {
+                tick: 5,
+                amount: 500,
+                author: "一番乗り",
+                message: "待機してました！",
+            },
+            DemoSuperchat {
+                tick: 18,
+                amount: 1000,
+                author: "もぐもぐ",
+                message: "神回の予感",
+            },
+            DemoSuperchat {
+                tick: 30,
+                amount: 200,
+                author: "ねこやしき",
+                message: "ナイス！",
+            },
+            DemoSuperchat {
+                tick: 42,
+                amount: 5000,
+                author: "石油王",
+                message: "おめでとう！",
+            },
+            DemoSuperchat {
+                tick: 60,
+                amount: 2000,
+                author: "さいたま在住",
+                message: "目標まであと少し",
+            },
+            DemoSuperchat {
+                tick: 75,
+                amount: 10000,
+                author: "赤スパの人",
+                message: "記念に！",
+            },
+            DemoSuperchat {
+                tick: 95,
+                amount: 500,
+                author: "一番乗り",
+                message: "最高でした",
+            },
+            DemoSuperchat {
+                tick: 110,
+                amount: 3000,
+                author: "もぐもぐ",
+                message: "お疲れさまでした！",
+            },
+        ],
+    },
+];
+
+impl DemoScenario {
+    pub fn find(name: &str) -> Result<&'static Self, String> {
+        DEMO_SCENARIOS
+            .iter()
+            .find(|scenario| scenario.name == name)
+            .ok_or_else(|| {
+                let names: Vec<_> = DEMO_SCENARIOS.iter().map(|s| s.name).collect();
+                format!(
+                    "Unknown demo scenario: {} (expected one of {})",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// 前半で最大値まで増え、以降は最大値の付近で揺れる同時接続者数
+    pub fn viewers_at(&self, tick: u32) -> i64 {
+        let ramp_ticks = (self.ticks / 2).max(1);
+        let ramped = if tick >= ramp_ticks {
+            self.viewers_peak
+        } else {
+            self.viewers_start
+                + (self.viewers_peak - self.viewers_start) * tick as i64 / ramp_ticks as i64
+        };
+        (ramped + VIEWER_WOBBLE[tick as usize % VIEWER_WOBBLE.len()]).max(0)
+    }
+
+    /// このステップで登録者が増えるか
+    pub fn gains_subscriber_at(&self, tick: u32) -> bool {
+        tick > 0 && tick.is_multiple_of(self.subscriber_every)
+    }
+
+    pub fn superchats_at(&self, tick: u32) -> impl Iterator<Item = &DemoSuperchat> {
+        self.superchats
+            .iter()
+            .filter(move |superchat| superchat.tick == tick)
+    }
+}
@@ -0,0 +1,94 @@
+use crate::history::Marker;
+use crate::points::MetricSnapshot;
+
+/// 履歴 CSV の見出し行
+const HISTORY_CSV_HEADER: &str = "timestamp,total,superchat,concurrent,likes,subscribers,manual,visitor,membership,bonus,superchat_amount,concurrent_viewers,like_count,initial_subscribers,current_subscribers,membership_count,markers";
+
+/// UNIX ミリ秒を ISO 8601 (UTC) の文字列にする
+pub fn format_iso8601(timestamp_ms: i64) -> String {
+    let seconds = timestamp_ms.div_euclid(1000);
+    let millis = timestamp_ms.rem_euclid(1000);
+    let days = seconds.div_euclid(86_400);
+    let time_of_day = seconds.rem_euclid(86_400);
+
+    // 1970-01-01 からの日数を年月日に変換する（グレゴリオ暦）
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60,
+        millis
+    )
+}
+
+/// カンマ・引用符・改行を含む値を引用符で囲む
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// ポイント更新の履歴を CSV にする
+///
+/// `markers` 列には、直前の行からその行までに追加されたマーカーのラベルを `; ` 区切りで入れる。
+pub fn history_csv(snapshots: &[MetricSnapshot], markers: &[Marker]) -> String {
+    let mut csv = String::from(HISTORY_CSV_HEADER);
+    csv.push('\n');
+
+    let mut previous_ms = i64::MIN;
+    for snapshot in snapshots {
+        let labels: Vec<&str> = markers
+            .iter()
+            .filter(|marker| {
+                let created_ms = marker.created_at as i64 * 1000;
+                created_ms > previous_ms && created_ms <= snapshot.timestamp
+            })
+            .map(|marker| marker.label.as_str())
+            .collect();
+        previous_ms = snapshot.timestamp;
+
+        let points = &snapshot.points;
+        let metrics = &snapshot.metrics;
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            format_iso8601(snapshot.timestamp),
+            points.total,
+            points.superchat,
+            points.concurrent,
+            points.likes,
+            points.subscribers,
+            points.manual,
+            points.visitor,
+            points.membership,
+            points.bonus,
+            metrics.superchat_amount,
+            metrics.concurrent_viewers,
+            metrics.like_count,
+            metrics.initial_subscribers,
+            metrics.current_subscribers,
+            metrics.membership_count,
+            csv_field(&labels.join("; "))
+        ));
+    }
+    csv
+}
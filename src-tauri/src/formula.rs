@@ -0,0 +1,332 @@
+use crate::points::{PointState, RawMetrics};
+
+/// 式の最大文字数
+const MAX_FORMULA_LENGTH: usize = 256;
+
+/// 括弧や単項演算子の最大ネスト数
+const MAX_NESTING_DEPTH: usize = 16;
+
+/// 式で使える変数
+#[derive(Debug, Clone, Copy)]
+enum Variable {
+    SuperchatAmount,
+    ConcurrentViewers,
+    LikeCount,
+    InitialSubscribers,
+    CurrentSubscribers,
+    NewSubscribers,
+    Subscribers,
+    Manual,
+    Visitor,
+    MembershipCount,
+    Membership,
+    Bonus,
+}
+
+impl Variable {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "superchat_amount" => Self::SuperchatAmount,
+            "concurrent_viewers" => Self::ConcurrentViewers,
+            "like_count" => Self::LikeCount,
+            "initial_subscribers" => Self::InitialSubscribers,
+            "current_subscribers" => Self::CurrentSubscribers,
+            "new_subscribers" => Self::NewSubscribers,
+            "subscribers" => Self::Subscribers,
+            "manual" => Self::Manual,
+            "visitor" => Self::Visitor,
+            "membership_count" => Self::MembershipCount,
+            "membership" => Self::Membership,
+            "bonus" => Self::Bonus,
+            _ => return None,
+        })
+    }
+
+    fn value(self, metrics: &RawMetrics, points: &PointState) -> f64 {
+        (match self {
+            Self::SuperchatAmount => metrics.superchat_amount,
+            Self::ConcurrentViewers => metrics.concurrent_viewers,
+            Self::LikeCount => metrics.like_count,
+            Self::InitialSubscribers => metrics.initial_subscribers,
+            Self::CurrentSubscribers => metrics.current_subscribers,
+            Self::NewSubscribers => metrics.current_subscribers - metrics.initial_subscribers,
+            Self::Subscribers => points.subscribers,
+            Self::Manual => points.manual,
+            Self::Visitor => points.visitor,
+            Self::MembershipCount => metrics.membership_count,
+            Self::Membership => points.membership,
+            Self::Bonus => points.bonus,
+        }) as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(f64),
+    Variable(Variable),
+    Negate(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Subtract(Box<Expr>, Box<Expr>),
+    Multiply(Box<Expr>, Box<Expr>),
+    Divide(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, metrics: &RawMetrics, points: &PointState) -> f64 {
+        match self {
+            Self::Number(n) => *n,
+            Self::Variable(v) => v.value(metrics, points),
+            Self::Negate(e) => -e.eval(metrics, points),
+            Self::Add(a, b) => a.eval(metrics, points) + b.eval(metrics, points),
+            Self::Subtract(a, b) => a.eval(metrics, points) - b.eval(metrics, points),
+            Self::Multiply(a, b) => a.eval(metrics, points) * b.eval(metrics, points),
+            Self::Divide(a, b) => a.eval(metrics, points) / b.eval(metrics, points),
+        }
+    }
+}
+
+/// 合計ポイントを計算する式
+///
+/// 数値、変数（`superchat_amount`, `concurrent_viewers`, `like_count`,
+/// `initial_subscribers`, `current_subscribers`, `new_subscribers`, `subscribers`,
+/// `manual`, `visitor`, `membership_count`, `membership`, `bonus`）、`+ - * /` と括弧のみを受け付ける。
+/// 例: `superchat_amount / 100 + concurrent_viewers * 2`
+#[derive(Debug, Clone)]
+pub struct Formula {
+    expr: Expr,
+}
+
+impl Formula {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        if source.len() > MAX_FORMULA_LENGTH {
+            return Err(format!(
+                "Formula is too long (max {} characters)",
+                MAX_FORMULA_LENGTH
+            ));
+        }
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+        let expr = parser.expr()?;
+        parser.skip_whitespace();
+        if parser.pos < parser.chars.len() {
+            return Err(format!(
+                "Unexpected '{}' at position {}",
+                parser.chars[parser.pos], parser.pos
+            ));
+        }
+        Ok(Self { expr })
+    }
+
+    /// 合計ポイントを計算する（0 除算などで有限の値にならなければ `None`）
+    pub fn evaluate(&self, metrics: &RawMetrics, points: &PointState) -> Option<i64> {
+        let value = self.expr.eval(metrics, points);
+        value.is_finite().then_some(value as i64)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.pos += 1;
+            let right = Box::new(self.term()?);
+            left = if op == '+' {
+                Expr::Add(Box::new(left), right)
+            } else {
+                Expr::Subtract(Box::new(left), right)
+            };
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut left = self.factor()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.pos += 1;
+            let right = Box::new(self.factor()?);
+            left = if op == '*' {
+                Expr::Multiply(Box::new(left), right)
+            } else {
+                Expr::Divide(Box::new(left), right)
+            };
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING_DEPTH {
+            return Err("Formula is nested too deeply".to_string());
+        }
+        let result = self.primary();
+        self.depth -= 1;
+        result
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                Ok(Expr::Negate(Box::new(self.factor()?)))
+            }
+            Some('(') => {
+                self.pos += 1;
+                let expr = self.expr()?;
+                if self.peek() != Some(')') {
+                    return Err(format!("Expected ')' at position {}", self.pos));
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let start = self.pos;
+                while self
+                    .chars
+                    .get(self.pos)
+                    .is_some_and(|c| c.is_ascii_digit() || *c == '.')
+                {
+                    self.pos += 1;
+                }
+                let literal: String = self.chars[start..self.pos].iter().collect();
+                literal
+                    .parse()
+                    .map(Expr::Number)
+                    .map_err(|_| format!("Invalid number '{}'", literal))
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let start = self.pos;
+                while self
+                    .chars
+                    .get(self.pos)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                Variable::parse(&name)
+                    .map(Expr::Variable)
+                    .ok_or_else(|| format!("Unknown variable '{}'", name))
+            }
+            Some(c) => Err(format!("Unexpected '{}' at position {}", c, self.pos)),
+            None => Err("Unexpected end of formula".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(source: &str) -> Option<i64> {
+        let metrics = RawMetrics {
+            superchat_amount: 1000,
+            concurrent_viewers: 30,
+            initial_subscribers: 100,
+            current_subscribers: 110,
+            ..Default::default()
+        };
+        let points = PointState {
+            manual: 2,
+            ..Default::default()
+        };
+        Formula::parse(source).unwrap().evaluate(&metrics, &points)
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        assert_eq!(evaluate("1 + 2 * 3"), Some(7));
+        assert_eq!(evaluate("(1 + 2) * 3"), Some(9));
+        assert_eq!(evaluate("10 - 4 - 3"), Some(3));
+        assert_eq!(evaluate("24 / 4 / 2"), Some(3));
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(evaluate("-5 + 2"), Some(-3));
+        assert_eq!(evaluate("2 * -3"), Some(-6));
+        assert_eq!(evaluate("--4"), Some(4));
+        assert_eq!(evaluate("-(1 + 2)"), Some(-3));
+    }
+
+    #[test]
+    fn variables_read_metrics_and_points() {
+        assert_eq!(
+            evaluate("superchat_amount / 100 + concurrent_viewers * 2"),
+            Some(70)
+        );
+        assert_eq!(evaluate("new_subscribers * manual"), Some(20));
+        assert_eq!(
+            Formula::parse("likes").err().as_deref(),
+            Some("Unknown variable 'likes'")
+        );
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert_eq!(
+            Formula::parse("1.2.3").err().as_deref(),
+            Some("Invalid number '1.2.3'")
+        );
+        assert!(Formula::parse("").is_err());
+        assert!(Formula::parse("1 +").is_err());
+        assert!(Formula::parse("(1 + 2").is_err());
+        assert!(Formula::parse("1 2").is_err());
+        assert!(Formula::parse("1 % 2").is_err());
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Formula::parse(&nested(MAX_NESTING_DEPTH - 1)).is_ok());
+        assert_eq!(
+            Formula::parse(&nested(MAX_NESTING_DEPTH)).err().as_deref(),
+            Some("Formula is nested too deeply")
+        );
+        // Unary minus nests too
+        let negated = |depth: usize| format!("{}1", "-".repeat(depth));
+        assert!(Formula::parse(&negated(MAX_NESTING_DEPTH - 1)).is_ok());
+        assert_eq!(
+            Formula::parse(&negated(MAX_NESTING_DEPTH)).err().as_deref(),
+            Some("Formula is nested too deeply")
+        );
+    }
+
+    #[test]
+    fn length_is_limited() {
+        let longest = format!("1{}", " + 1".repeat((MAX_FORMULA_LENGTH - 1) / 4));
+        assert!(longest.len() <= MAX_FORMULA_LENGTH);
+        assert!(Formula::parse(&longest).is_ok());
+        assert!(
+            Formula::parse(&"1".repeat(MAX_FORMULA_LENGTH + 1))
+                .unwrap_err()
+                .starts_with("Formula is too long")
+        );
+    }
+
+    #[test]
+    fn non_finite_results_are_none() {
+        assert_eq!(evaluate("1 / 0"), None);
+        assert_eq!(evaluate("0 / 0"), None);
+        assert_eq!(evaluate("superchat_amount / like_count"), None);
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::config::{APP_DIR_NAME, SESSION_HISTORY_LIMIT};
+use crate::points::{RawMetrics, SessionAnalytics};
+
+/// 配信履歴の保存先ディレクトリ名
+const HISTORY_DIR_NAME: &str = "sessions";
+
+/// 配信履歴の一覧ファイル名
+const INDEX_FILE_NAME: &str = "index.json";
+
+/// 配信履歴の一覧に載せる概要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEntry {
+    /// 履歴 ID（詳細ファイル名にも使う）
+    pub id: String,
+    pub video_id: String,
+    /// 配信タイトル
+    pub title: String,
+    /// 監視開始時刻（UNIX 秒）
+    pub started_at: u64,
+    /// 監視終了時刻（UNIX 秒）
+    pub ended_at: u64,
+    /// 最終ポイント
+    pub final_points: i64,
+    /// 最大同時接続者数
+    pub peak_viewers: i64,
+}
+
+/// 配信中に付けたマーカー（見どころの目印）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Marker {
+    /// 監視開始からの経過秒数
+    pub elapsed_seconds: u64,
+    pub label: String,
+    /// 付けた時刻（UNIX 秒）
+    pub created_at: u64,
+}
+
+/// 配信 1 回分の詳細
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    #[serde(flatten)]
+    pub entry: SessionEntry,
+    pub metrics: RawMetrics,
+    pub analytics: SessionAnalytics,
+    /// マーカー（古い順）
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+}
+
+/// 配信履歴の保存先ディレクトリ
+pub fn history_dir() -> Result<PathBuf, String> {
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_DIR_NAME).join(HISTORY_DIR_NAME))
+        .ok_or_else(|| "Data directory not available".to_string())
+}
+
+fn record_path(dir: &std::path::Path, id: &str) -> Result<PathBuf, String> {
+    // IDs come from the frontend, so keep them from escaping the history directory
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid session id: {}", id));
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+fn read_index(dir: &std::path::Path) -> Result<Vec<SessionEntry>, String> {
+    let path = dir.join(INDEX_FILE_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// 配信履歴を新しい順に返す
+pub fn list_sessions() -> Result<Vec<SessionEntry>, String> {
+    read_index(&history_dir()?)
+}
+
+/// 配信 1 回分の詳細を読み込む
+pub fn load_session(id: &str) -> Result<SessionRecord, String> {
+    let path = record_path(&history_dir()?, id)?;
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// 配信を履歴に追加し、上限を超えた古い履歴を削除する
+pub fn record_session(record: &SessionRecord) -> Result<(), String> {
+    let dir = history_dir()?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let path = record_path(&dir, &record.entry.id)?;
+    let content = serde_json::to_string_pretty(record).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    // An unreadable index is replaced rather than blocking new history
+    let mut index = read_index(&dir).unwrap_or_else(|e| {
+        eprintln!("Discarding session index: {}", e);
+        Vec::new()
+    });
+    index.retain(|entry| entry.id != record.entry.id);
+    index.insert(0, record.entry.clone());
+
+    if index.len() > SESSION_HISTORY_LIMIT {
+        for pruned in index.split_off(SESSION_HISTORY_LIMIT) {
+            if let Ok(path) = record_path(&dir, &pruned.id) {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    let path = dir.join(INDEX_FILE_NAME);
+    let content = serde_json::to_string_pretty(&index).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
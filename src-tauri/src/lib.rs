@@ -1,624 +1,3787 @@
 mod config;
+mod cookies;
+mod demo;
+mod export;
+mod formula;
+mod history;
 mod points;
 mod sidecar;
+mod state;
+mod storage;
+mod stream;
 mod web_server;
 
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
 use tauri::{Emitter, Manager, State, WebviewWindowBuilder, webview::Cookie};
 use tokio::sync::{RwLock, broadcast, mpsc};
-use tokio::time::{Duration, interval};
+use tokio::time::{Duration, interval, interval_at};
 
-use sidecar::SidecarManager;
-use web_server::PointsPayload;
+use sidecar::{SidecarManager, SidecarStatus};
+use stream::StreamSession;
+use web_server::{OverlayMessage, PointsPayload, WebEvent};
 
 pub struct AppState {
     pub is_monitoring: RwLock<bool>,
+    /// Monitoring is running but polling and point updates are on hold
+    pub is_paused: RwLock<bool>,
     pub points: RwLock<points::PointState>,
-    pub sidecar: RwLock<Option<SidecarManager>>,
+    /// Sidecar started by `prewarm_sidecar`, taken by the next `start_monitoring`
+    pub prewarmed_sidecar: RwLock<Option<SidecarManager>>,
+    /// Streams being monitored, keyed by video ID
+    pub streams: RwLock<HashMap<String, Arc<StreamSession>>>,
+    /// Metrics of every stream added together; points are calculated from these
     pub raw_metrics: RwLock<points::RawMetrics>,
+    /// The first stream of the session; the saved session and history are keyed on it
     pub monitoring_video_id: RwLock<Option<String>>,
-    pub monitoring_channel_id: RwLock<Option<String>>,
     pub is_authenticated: RwLock<bool>,
-    pub web_broadcast: broadcast::Sender<PointsPayload>,
+    pub web_broadcast: broadcast::Sender<WebEvent>,
     pub server_url: RwLock<Option<String>>,
     pub concurrent_bonus_given: RwLock<bool>,
+    /// Superchat milestones whose bonus was already awarded this session
+    pub milestones_reached: RwLock<Vec<config::Milestone>>,
+    pub config: RwLock<config::Config>,
+    pub superchat_stats: RwLock<points::SuperchatStats>,
+    pub last_metrics_update: RwLock<Option<Instant>>,
+    pub monitoring_started_at: RwLock<Option<Instant>>,
+    pub sidecar_restart_count: RwLock<u32>,
+    pub last_restart_reason: RwLock<Option<String>>,
+    pub session_counters: RwLock<points::SessionCounters>,
+    pub imported_cookies: RwLock<Option<String>>,
+    pub start_generation: AtomicU64,
+    pub broadcast_throttle: RwLock<BroadcastThrottle>,
+    pub latest_points: RwLock<Option<PointsPayload>>,
+    pub last_superchat_timing: RwLock<Option<SuperchatTiming>>,
+    pub clock_skew_reported: RwLock<bool>,
+    pub cap_reached: RwLock<bool>,
+    /// Whether the latest subscriber count came from the exact (authenticated) source
+    pub using_exact_subscribers: RwLock<bool>,
+    pub goals_reached: RwLock<usize>,
+    pub broadcast_stats: RwLock<BroadcastStats>,
+    pub markers: RwLock<Vec<history::Marker>>,
+    pub metric_history: RwLock<VecDeque<points::MetricSample>>,
+    /// Every points update of the session, for reviewing it afterwards
+    pub history: RwLock<VecDeque<points::MetricSnapshot>>,
+    /// Sidecar health as of the last ping, sent as `sidecar-status` when it changes
+    pub sidecar_status: RwLock<SidecarStatus>,
 }
 
+impl AppState {
+    /// State at startup, holding the points of `saved_session` until it is resumed
+    fn new(config: config::Config, saved_session: state::SessionState) -> Self {
+        // Create broadcast channel for web clients
+        let (web_broadcast, _) = broadcast::channel::<WebEvent>(16);
+        // Milestones the restored superchat total already passed are not awarded again
+        let milestones_reached = saved_session
+            .metrics
+            .as_ref()
+            .map(|metrics| {
+                config
+                    .points
+                    .milestones_reached_at(metrics.superchat_amount)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            is_monitoring: RwLock::new(false),
+            is_paused: RwLock::new(false),
+            points: RwLock::new(saved_session.points),
+            prewarmed_sidecar: RwLock::new(None),
+            streams: RwLock::new(HashMap::new()),
+            raw_metrics: RwLock::new(saved_session.metrics.unwrap_or_default()),
+            monitoring_video_id: RwLock::new(None),
+            is_authenticated: RwLock::new(false),
+            web_broadcast,
+            server_url: RwLock::new(None),
+            concurrent_bonus_given: RwLock::new(saved_session.concurrent_bonus_given),
+            milestones_reached: RwLock::new(milestones_reached),
+            config: RwLock::new(config),
+            superchat_stats: RwLock::new(points::SuperchatStats::default()),
+            last_metrics_update: RwLock::new(None),
+            monitoring_started_at: RwLock::new(None),
+            sidecar_restart_count: RwLock::new(0),
+            last_restart_reason: RwLock::new(None),
+            session_counters: RwLock::new(points::SessionCounters::default()),
+            imported_cookies: RwLock::new(None),
+            start_generation: AtomicU64::new(0),
+            broadcast_throttle: RwLock::new(BroadcastThrottle::default()),
+            latest_points: RwLock::new(None),
+            last_superchat_timing: RwLock::new(None),
+            clock_skew_reported: RwLock::new(false),
+            cap_reached: RwLock::new(false),
+            using_exact_subscribers: RwLock::new(false),
+            goals_reached: RwLock::new(0),
+            broadcast_stats: RwLock::new(BroadcastStats::default()),
+            markers: RwLock::new(saved_session.markers),
+            metric_history: RwLock::new(VecDeque::new()),
+            history: RwLock::new(VecDeque::new()),
+            sidecar_status: RwLock::new(SidecarStatus::Stopped),
+        }
+    }
+}
+
+/// Delivery counts for events sent to web clients
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct BroadcastStats {
+    /// Events delivered to at least one overlay
+    pub total_sent: u64,
+    /// Events dropped because no overlay was connected
+    pub total_dropped: u64,
+    /// Overlays connected at the last send
+    pub last_receiver_count: usize,
+    /// Unix seconds of the last send attempt
+    pub last_attempt_at: Option<u64>,
+}
+
+/// Coalesces web client broadcasts sent within `server.min_broadcast_interval_ms`
+#[derive(Default)]
+pub struct BroadcastThrottle {
+    last_sent: Option<Instant>,
+    /// Latest payload waiting for the scheduled flush
+    pending: Option<PointsPayload>,
+}
+
+/// Starts monitoring a stream. While a stream is already being monitored, the new one is
+/// added to the same session and its points count toward the same total (e.g. collabs).
 #[tauri::command]
 async fn start_monitoring(
     video_url: String,
+    chat_id: Option<String>,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    start_monitoring_from(video_url, chat_id, None, state, app).await
+}
+
+/// Starts monitoring, carrying over the metrics of `saved` when it is the same video
+async fn start_monitoring_from(
+    video_url: String,
+    chat_id: Option<String>,
+    saved: Option<state::SessionState>,
     state: State<'_, Arc<AppState>>,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
+    // Extract video ID
+    let video_id = sidecar::extract_video_id(&video_url)?;
+    let resumed = saved.as_ref().filter(|session| session.is_for(&video_id));
+    let resumed_metrics = resumed.and_then(|session| session.resumable_metrics(&video_id));
+
     {
         let monitoring = state.is_monitoring.read().await;
         if *monitoring {
-            return Err("Already monitoring".into());
+            check_can_add_stream(&state, &video_id).await?;
         }
     }
-
-    // Extract video ID
-    let video_id = sidecar::extract_video_id(&video_url)?;
     println!("Starting monitoring for video: {}", video_id);
 
-    // Create superchat event channel
-    let (superchat_tx, mut superchat_rx) = mpsc::unbounded_channel();
-
-    // Start sidecar
-    let mut sidecar = SidecarManager::new();
-    sidecar.set_superchat_handler(superchat_tx);
-    sidecar.start(&app).await?;
+    // Validate the optional chat ID before spawning anything
+    let chat_id = match chat_id.as_deref().map(str::trim) {
+        Some(id) if !id.is_empty() => Some(sidecar::extract_video_id(id)?),
+        _ => None,
+    };
 
-    // Try to get cookies from YouTube login window for authentication
-    if let Some(login_window) = app.get_webview_window("youtube-login") {
-        let url: url::Url = "https://www.youtube.com".parse().unwrap();
-        if let Ok(cookies) = login_window.cookies_for_url(url) {
-            let cookie_str: String = cookies
-                .iter()
-                .map(|c| format!("{}={}", c.name(), c.value()))
-                .collect::<Vec<_>>()
-                .join("; ");
-            if !cookie_str.is_empty()
-                && let Err(e) = sidecar.set_cookies(&cookie_str).await
-            {
-                eprintln!("Failed to set cookies: {}", e);
-            }
-        }
-    }
+    // stop_monitoring bumps this, cancelling a start that is still in progress
+    let start_generation = state.start_generation.load(Ordering::SeqCst);
 
-    // Initialize YouTube client
-    let is_authenticated = sidecar.init().await?;
-    println!(
-        "YouTube client initialized (authenticated: {})",
-        is_authenticated
-    );
+    // Create superchat and membership event channels
+    let (superchat_tx, superchat_rx) = mpsc::unbounded_channel();
+    let (membership_tx, membership_rx) = mpsc::unbounded_channel();
 
-    // Store authentication status
-    {
-        let mut auth = state.is_authenticated.write().await;
-        *auth = is_authenticated;
-    }
+    // Reuse a sidecar started by prewarm_sidecar, otherwise start one
+    let (mut sidecar, prewarmed_auth) = match take_prewarmed_sidecar(&state).await {
+        Some((sidecar, is_authenticated)) => (sidecar, Some(is_authenticated)),
+        None => {
+            let mut sidecar = new_sidecar(&state).await;
+            sidecar.start(&app).await?;
+            apply_stored_cookies(&sidecar, &state, &app).await;
+            (sidecar, None)
+        }
+    };
+    sidecar.set_superchat_handler(superchat_tx).await;
+    sidecar.set_membership_handler(membership_tx).await;
+    let (termination_tx, termination_rx) = mpsc::unbounded_channel();
+    sidecar.set_termination_handler(termination_tx).await;
 
-    // Get initial live info
-    let live_info = sidecar.get_live_info(&video_id).await?;
-    if !live_info.is_live {
-        sidecar.stop().await?;
-        return Err("The video is not a live stream".into());
-    }
+    // The exact count belongs to the logged-in channel, so only the first stream uses it
+    let allow_exact_count = state.monitoring_video_id.read().await.is_none();
 
-    let channel_id = live_info.channel_id.clone();
+    // Nothing is stored in state until every startup step succeeded
+    let startup = prepare_sidecar(
+        &mut sidecar,
+        &video_id,
+        chat_id.as_deref(),
+        prewarmed_auth,
+        allow_exact_count,
+    )
+    .await?;
+    let live_info = startup.live_info;
 
-    // Get initial subscriber count - use exact count if authenticated
-    let initial_subscribers = if is_authenticated {
-        match sidecar.get_exact_subscriber_count().await {
-            Ok(count) => {
-                println!("Got exact subscriber count: {}", count);
-                count
-            }
-            Err(e) => {
-                eprintln!("Failed to get exact subscriber count, falling back: {}", e);
-                sidecar.get_subscriber_count(&channel_id).await?
-            }
+    let stream = {
+        let mut monitoring = state.is_monitoring.write().await;
+        let abort_reason = if state.start_generation.load(Ordering::SeqCst) != start_generation {
+            Err("Monitoring start was cancelled".to_string())
+        } else if *monitoring {
+            // Another start completed while this one was initializing
+            check_can_add_stream(&state, &video_id).await
+        } else {
+            Ok(())
+        };
+        if let Err(reason) = abort_reason {
+            drop(monitoring);
+            let _ = sidecar.stop().await;
+            return Err(reason);
         }
-    } else {
-        sidecar.get_subscriber_count(&channel_id).await?
-    };
 
-    // Initialize raw metrics
-    {
-        let mut metrics = state.raw_metrics.write().await;
-        *metrics = points::RawMetrics {
+        let mut metrics = points::RawMetrics {
             superchat_amount: 0,
-            concurrent_viewers: live_info.concurrent_viewers,
+            concurrent_viewers: live_info.concurrent_viewers.unwrap_or(0),
             like_count: live_info.like_count.unwrap_or(0),
-            initial_subscribers,
-            current_subscribers: initial_subscribers,
+            like_high: live_info.like_count.unwrap_or(0),
+            initial_subscribers: startup.initial_subscribers,
+            current_subscribers: startup.initial_subscribers,
+            membership_count: 0,
+            extra_metrics: live_info.extra_metrics.clone().unwrap_or_default(),
         };
-    }
+        // Growth during the interrupted run keeps counting from the original baseline
+        if let Some(saved) = resumed_metrics {
+            metrics.resume_from(saved);
+            println!(
+                "Resuming {} from subscriber baseline {}",
+                video_id, metrics.initial_subscribers
+            );
+        }
+        let stream = Arc::new(StreamSession::new(
+            video_id.clone(),
+            chat_id.clone(),
+            live_info.clone(),
+            sidecar,
+            metrics,
+            allow_exact_count,
+        ));
 
-    // Start live chat monitoring
-    sidecar.start_live_chat(&video_id).await?;
+        // Store authentication status
+        {
+            let mut auth = state.is_authenticated.write().await;
+            *auth = startup.is_authenticated;
+        }
+
+        // A stream added to a running session keeps the session's points and history
+        if !*monitoring {
+            reset_session(&state, &video_id, startup.using_exact_subscribers, resumed).await;
+        }
+        {
+            let mut streams = state.streams.write().await;
+            streams.insert(video_id.clone(), stream.clone());
+        }
+        sync_combined_metrics(&state).await;
+        if let Some(viewers) = live_info.concurrent_viewers {
+            let mut counters = state.session_counters.write().await;
+            counters.record_viewers(viewers);
+        }
 
-    // Store sidecar and monitoring info
-    {
-        let mut sidecar_guard = state.sidecar.write().await;
-        *sidecar_guard = Some(sidecar);
-    }
-    {
-        let mut vid = state.monitoring_video_id.write().await;
-        *vid = Some(video_id.clone());
-    }
-    {
-        let mut cid = state.monitoring_channel_id.write().await;
-        *cid = Some(channel_id.clone());
-    }
-    {
-        let mut monitoring = state.is_monitoring.write().await;
         *monitoring = true;
-    }
+        stream
+    };
 
     // Emit initial points
     emit_points(&state, &app).await;
+    emit_auth_status(&state, &app).await;
+    let _ = app.emit("streams-changed", list_streams(&state).await);
 
-    // Spawn superchat handler
-    let state_clone = state.inner().clone();
-    let app_clone = app.clone();
-    tauri::async_runtime::spawn(async move {
-        while let Some(superchat) = superchat_rx.recv().await {
-            println!(
-                "Superchat received: {} from {} - {}",
-                superchat.amount, superchat.author, superchat.message
-            );
-
-            // Add superchat amount to metrics
-            {
-                let mut metrics = state_clone.raw_metrics.write().await;
-                metrics.superchat_amount += superchat.amount;
-            }
-
-            // Recalculate and emit points
-            emit_points(&state_clone, &app_clone).await;
-
-            // Also emit superchat event for UI effects
-            let _ = app_clone.emit("superchat", &superchat);
-        }
-    });
+    spawn_superchat_handler(
+        state.inner().clone(),
+        app.clone(),
+        Some(stream.clone()),
+        superchat_rx,
+    );
+    spawn_membership_handler(
+        state.inner().clone(),
+        app.clone(),
+        stream.clone(),
+        membership_rx,
+    );
+    spawn_sidecar_supervisor(
+        state.inner().clone(),
+        app.clone(),
+        stream.clone(),
+        termination_rx,
+    );
+    if state.monitoring_video_id.read().await.as_deref() == Some(video_id.as_str()) {
+        spawn_sidecar_health_check(state.inner().clone(), app.clone(), start_generation);
+    }
 
     // Spawn polling task
     let state_clone = state.inner().clone();
     let app_clone = app.clone();
     tauri::async_runtime::spawn(async move {
-        let mut ticker = interval(Duration::from_secs(config::POLLING_INTERVAL_SECONDS));
+        let mut period = state_clone.config.read().await.polling.min_interval_seconds;
+        let mut ticker = interval(Duration::from_secs(period));
 
         loop {
             ticker.tick().await;
 
-            // Check if still monitoring
-            if !*state_clone.is_monitoring.read().await {
+            // Check if still monitoring (and not superseded by a newer session or removed)
+            if !*state_clone.is_monitoring.read().await
+                || state_clone.start_generation.load(Ordering::SeqCst) != start_generation
+                || !is_active_stream(&state_clone, &stream).await
+            {
                 break;
             }
+            if *state_clone.is_paused.read().await {
+                continue;
+            }
+
+            let before = polled_metrics(&stream).await;
 
             // Update metrics
-            if let Err(e) = update_metrics(&state_clone).await {
-                eprintln!("Failed to update metrics: {}", e);
-                continue;
+            match update_metrics(&state_clone, &app_clone, &stream).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    handle_stream_end(&state_clone, &app_clone, &stream).await;
+                    break;
+                }
+                Err(e) => {
+                    eprintln!("Failed to update metrics: {}", e);
+                    emit_monitoring_error(&app_clone, "update_metrics", &e);
+                    continue;
+                }
             }
 
             // Emit updated points
             emit_points(&state_clone, &app_clone).await;
+            record_metric_sample(&state_clone).await;
+
+            // Back off while the stream is quiet, and poll quickly again once it changes
+            let changed = polled_metrics(&stream).await != before;
+            let next = state_clone
+                .config
+                .read()
+                .await
+                .polling
+                .next_interval_seconds(period, changed);
+            if next != period {
+                period = next;
+                let next = Duration::from_secs(period);
+                ticker = interval_at(tokio::time::Instant::now() + next, next);
+            }
         }
 
-        println!("Polling task stopped");
+        println!("Polling task stopped: {}", stream.video_id);
     });
 
     println!("Monitoring started for: {}", video_id);
     Ok(())
 }
 
-async fn update_metrics(state: &Arc<AppState>) -> Result<(), String> {
-    let video_id = {
-        let vid = state.monitoring_video_id.read().await;
-        vid.clone().ok_or("No video ID")?
-    };
-    let channel_id = {
-        let cid = state.monitoring_channel_id.read().await;
-        cid.clone().ok_or("No channel ID")?
-    };
-    let is_authenticated = *state.is_authenticated.read().await;
-
-    let sidecar_guard = state.sidecar.read().await;
-    let sidecar = sidecar_guard.as_ref().ok_or("Sidecar not running")?;
+/// Metrics whose changes keep the polling interval short
+async fn polled_metrics(stream: &StreamSession) -> (i64, i64) {
+    let metrics = stream.metrics.read().await;
+    (metrics.concurrent_viewers, metrics.like_count)
+}
 
-    // Get live info
-    let live_info = sidecar.get_live_info(&video_id).await?;
+/// Whether another stream can join the running session
+async fn check_can_add_stream(state: &Arc<AppState>, video_id: &str) -> Result<(), String> {
+    // Demos run without a stream to add to
+    if state.monitoring_video_id.read().await.is_none() {
+        return Err("Already monitoring".into());
+    }
+    if state.streams.read().await.contains_key(video_id) {
+        return Err(format!("Already monitoring {}", video_id));
+    }
+    Ok(())
+}
 
-    // Get current subscriber count - use exact count if authenticated
-    let current_subscribers = if is_authenticated {
-        match sidecar.get_exact_subscriber_count().await {
-            Ok(count) => count,
-            Err(e) => {
-                eprintln!("Failed to get exact subscriber count, falling back: {}", e);
-                sidecar.get_subscriber_count(&channel_id).await?
-            }
-        }
-    } else {
-        sidecar.get_subscriber_count(&channel_id).await?
-    };
+/// Starts a session on its first stream. A `resumed` session keeps its markers, history and
+/// concurrent bonus; otherwise they start empty.
+async fn reset_session(
+    state: &Arc<AppState>,
+    video_id: &str,
+    using_exact_subscribers: bool,
+    resumed: Option<&state::SessionState>,
+) {
+    {
+        let mut using_exact = state.using_exact_subscribers.write().await;
+        *using_exact = using_exact_subscribers;
+    }
+    {
+        let mut last_update = state.last_metrics_update.write().await;
+        *last_update = Some(Instant::now());
+    }
+    {
+        let mut counters = state.session_counters.write().await;
+        *counters = points::SessionCounters::default();
+    }
+    {
+        let mut vid = state.monitoring_video_id.write().await;
+        *vid = Some(video_id.to_string());
+    }
 
-    // Update metrics
+    // Reset session diagnostics
     {
-        let mut metrics = state.raw_metrics.write().await;
-        metrics.concurrent_viewers = live_info.concurrent_viewers;
-        metrics.like_count = live_info.like_count.unwrap_or(0);
-        metrics.current_subscribers = current_subscribers;
+        let mut started_at = state.monitoring_started_at.write().await;
+        *started_at = Some(Instant::now());
+    }
+    {
+        let mut restart_count = state.sidecar_restart_count.write().await;
+        *restart_count = 0;
+    }
+    {
+        let mut reason = state.last_restart_reason.write().await;
+        *reason = None;
+    }
+    {
+        let mut timing = state.last_superchat_timing.write().await;
+        *timing = None;
+    }
+    {
+        let mut skew_reported = state.clock_skew_reported.write().await;
+        *skew_reported = false;
+    }
+    {
+        let mut paused = state.is_paused.write().await;
+        *paused = false;
     }
 
-    Ok(())
+    if let Some(saved) = resumed {
+        *state.markers.write().await = saved.markers.clone();
+        *state.concurrent_bonus_given.write().await = saved.concurrent_bonus_given;
+        return;
+    }
+    {
+        let mut markers = state.markers.write().await;
+        markers.clear();
+    }
+    {
+        let mut history = state.metric_history.write().await;
+        history.clear();
+    }
+    {
+        let mut history = state.history.write().await;
+        history.clear();
+    }
 }
 
-#[derive(Clone, serde::Serialize)]
-struct PointsUpdatePayload {
-    points: points::PointState,
-    metrics: points::RawMetrics,
-    config: config::PointsConfig,
+/// Whether `stream` is still part of the session (not stopped or replaced)
+async fn is_active_stream(state: &Arc<AppState>, stream: &Arc<StreamSession>) -> bool {
+    state
+        .streams
+        .read()
+        .await
+        .get(&stream.video_id)
+        .is_some_and(|active| Arc::ptr_eq(active, stream))
 }
 
-async fn emit_points(state: &Arc<AppState>, app: &tauri::AppHandle) {
-    let (points, metrics) = {
-        let metrics = state.raw_metrics.read().await;
-        let mut calculated =
-            points::PointState::calculate_from_metrics(&metrics, &config::POINTS_CONFIG);
-
-        // Check concurrent bonus (50人超えたら1回だけ1000円)
-        let mut bonus_given = state.concurrent_bonus_given.write().await;
-        if metrics.concurrent_viewers > 50 && !*bonus_given {
-            *bonus_given = true;
-        }
-        calculated.concurrent = if *bonus_given { 1000 } else { 0 };
-
-        // Add manual points, visitor points, and subscriber points (all manual)
-        let current_points = state.points.read().await;
-        calculated.manual = current_points.manual;
-        calculated.visitor = current_points.visitor;
-        // 新規登録者は手動入力の値を使用
-        calculated.subscribers = current_points.subscribers;
+/// The first stream of the session, which the saved session and history are keyed on
+async fn primary_stream(state: &Arc<AppState>) -> Option<Arc<StreamSession>> {
+    let video_id = state.monitoring_video_id.read().await.clone()?;
+    state.streams.read().await.get(&video_id).cloned()
+}
 
-        // Recalculate total
-        calculated.total = calculated.superchat + calculated.concurrent + calculated.likes;
-        calculated.total +=
-            (current_points.subscribers as f64 / config::POINTS_CONFIG.subscriber_rate) as i64;
-        calculated.total +=
-            (current_points.manual as f64 * config::POINTS_CONFIG.manual_rate) as i64;
-        calculated.total +=
-            (current_points.visitor as f64 * config::POINTS_CONFIG.visitor_rate) as i64;
+/// The stream with `video_id`, or the first stream when it is `None`
+async fn find_stream(
+    state: &Arc<AppState>,
+    video_id: Option<&str>,
+) -> Result<Arc<StreamSession>, String> {
+    match video_id {
+        Some(video_id) => state
+            .streams
+            .read()
+            .await
+            .get(video_id)
+            .cloned()
+            .ok_or_else(|| format!("Not monitoring {}", video_id)),
+        None => primary_stream(state)
+            .await
+            .ok_or_else(|| "Not monitoring".into()),
+    }
+}
 
-        // Update stored points
-        drop(current_points);
-        let mut points_guard = state.points.write().await;
-        *points_guard = calculated.clone();
+async fn all_streams(state: &Arc<AppState>) -> Vec<Arc<StreamSession>> {
+    state.streams.read().await.values().cloned().collect()
+}
 
-        (calculated, metrics.clone())
-    };
+async fn list_streams(state: &Arc<AppState>) -> Vec<stream::StreamInfo> {
+    let mut streams = Vec::new();
+    for stream in all_streams(state).await {
+        streams.push(stream.info().await);
+    }
+    streams.sort_by(|a, b| a.video_id.cmp(&b.video_id));
+    streams
+}
 
-    let payload = PointsUpdatePayload {
-        points: points.clone(),
-        metrics: metrics.clone(),
-        config: config::POINTS_CONFIG.clone(),
-    };
-    let _ = app.emit("points-update", &payload);
+/// Rebuilds the combined metrics after a stream's metrics changed
+async fn sync_combined_metrics(state: &Arc<AppState>) {
+    let mut all = Vec::new();
+    for stream in all_streams(state).await {
+        all.push(stream.metrics.read().await.clone());
+    }
+    let mut metrics = state.raw_metrics.write().await;
+    *metrics = points::RawMetrics::combine(&all);
+}
 
-    // Broadcast to web clients
-    let _ = state.web_broadcast.send(PointsPayload {
-        points,
-        metrics,
-        config: config::POINTS_CONFIG.clone(),
-    });
+/// Lists the streams being monitored with their own metrics.
+#[tauri::command]
+async fn get_streams(state: State<'_, Arc<AppState>>) -> Result<Vec<stream::StreamInfo>, String> {
+    Ok(list_streams(&state).await)
 }
 
+/// Returns the last `getLiveInfo` result for a stream (the first one when `video_id` is omitted),
+/// or `None` when not monitoring it.
 #[tauri::command]
-async fn stop_monitoring(state: State<'_, Arc<AppState>>) -> Result<(), String> {
-    {
-        let mut monitoring = state.is_monitoring.write().await;
-        *monitoring = false;
+async fn get_stream_info(
+    state: State<'_, Arc<AppState>>,
+    video_id: Option<String>,
+) -> Result<Option<sidecar::LiveInfo>, String> {
+    match find_stream(&state, video_id.as_deref()).await {
+        Ok(stream) => Ok(Some(stream.live_info.read().await.clone())),
+        Err(_) => Ok(None),
     }
+}
 
-    // Stop sidecar
-    {
-        let mut sidecar_guard = state.sidecar.write().await;
-        if let Some(mut sidecar) = sidecar_guard.take() {
-            sidecar.stop().await?;
-        }
-    }
+#[derive(Clone, serde::Serialize)]
+struct MonitorError {
+    /// Which step failed (e.g. `update_metrics`, `subscriber_count`)
+    context: &'static str,
+    message: String,
+}
 
-    // Clear monitoring info
-    {
-        let mut vid = state.monitoring_video_id.write().await;
-        *vid = None;
-    }
-    {
-        let mut cid = state.monitoring_channel_id.write().await;
-        *cid = None;
-    }
+/// Tells the frontend about a failure in a background monitoring task, which otherwise
+/// only shows up in the log. The task keeps running.
+fn emit_monitoring_error(app: &tauri::AppHandle, context: &'static str, message: &str) {
+    let _ = app.emit(
+        "monitoring-error",
+        MonitorError {
+            context,
+            message: message.to_string(),
+        },
+    );
+}
 
-    println!("Monitoring stopped");
-    Ok(())
+#[derive(Clone, serde::Serialize)]
+pub struct SuperchatTiming {
+    /// Timestamp reported by the sidecar (ms since the epoch)
+    reported_timestamp: i64,
+    /// Host time when the event was received (ms since the epoch)
+    received_at: i64,
+    /// `reported_timestamp - received_at`; positive means the sidecar clock runs ahead
+    skew_ms: i64,
+    /// Whether the timestamp was replaced with `received_at`
+    normalized: bool,
 }
 
-#[tauri::command]
-async fn add_manual_points(
-    amount: i64,
-    state: State<'_, Arc<AppState>>,
-    app: tauri::AppHandle,
-) -> Result<(), String> {
-    let (points, metrics) = {
-        let mut points = state.points.write().await;
-        points.manual += amount;
-        points.total += (amount as f64 * config::POINTS_CONFIG.manual_rate) as i64;
-        let metrics = state.raw_metrics.read().await;
-        (points.clone(), metrics.clone())
+/// Replaces a superchat timestamp that is too far from the host clock with the receive time
+async fn check_superchat_clock(
+    state: &Arc<AppState>,
+    app: &tauri::AppHandle,
+    superchat: &mut sidecar::SuperchatEventData,
+) {
+    let received_at = unix_now_ms();
+    let skew_ms = superchat.timestamp - received_at;
+    let normalized = skew_ms.abs() > config::CLOCK_SKEW_THRESHOLD_SECONDS * 1000;
+    let timing = SuperchatTiming {
+        reported_timestamp: superchat.timestamp,
+        received_at,
+        skew_ms,
+        normalized,
     };
 
-    println!("Added {} manual points. Total: {}", amount, points.total);
-
-    // Emit event with full payload (points + metrics)
-    let payload = PointsUpdatePayload {
-        points: points.clone(),
-        metrics: metrics.clone(),
-        config: config::POINTS_CONFIG.clone(),
-    };
-    let _ = app.emit("points-update", &payload);
+    if normalized {
+        superchat.timestamp = received_at;
+        let mut skew_reported = state.clock_skew_reported.write().await;
+        if !*skew_reported {
+            *skew_reported = true;
+            eprintln!(
+                "Superchat timestamp skewed by {} ms; using receive time",
+                skew_ms
+            );
+            let _ = app.emit("clock-skew", &timing);
+        }
+    }
 
-    // Broadcast to web clients
-    let _ = state.web_broadcast.send(PointsPayload {
-        points,
-        metrics,
-        config: config::POINTS_CONFIG.clone(),
-    });
+    let mut last_timing = state.last_superchat_timing.write().await;
+    *last_timing = Some(timing);
+}
 
-    Ok(())
+/// Whether the most recent poll used the exact subscriber count (rather than the public,
+/// rounded one). Falls back to `false` whenever a poll could not get the exact count.
+#[tauri::command]
+async fn get_using_exact_subscribers(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(*state.using_exact_subscribers.read().await)
 }
 
+/// Timing details of the most recent superchat, for diagnosing sidecar clock skew
 #[tauri::command]
-async fn add_visitor_points(
-    amount: i64,
+async fn get_last_superchat_timestamp(
     state: State<'_, Arc<AppState>>,
-    app: tauri::AppHandle,
-) -> Result<(), String> {
-    let (points, metrics) = {
-        let mut points = state.points.write().await;
-        points.visitor += amount;
-        points.total += (amount as f64 * config::POINTS_CONFIG.visitor_rate) as i64;
-        let metrics = state.raw_metrics.read().await;
-        (points.clone(), metrics.clone())
-    };
-
-    println!("Added {} visitor points. Total: {}", amount, points.total);
-
-    // Emit event with full payload (points + metrics)
-    let payload = PointsUpdatePayload {
-        points: points.clone(),
-        metrics: metrics.clone(),
-        config: config::POINTS_CONFIG.clone(),
-    };
-    let _ = app.emit("points-update", &payload);
+) -> Result<Option<SuperchatTiming>, String> {
+    Ok(state.last_superchat_timing.read().await.clone())
+}
 
-    // Broadcast to web clients
-    let _ = state.web_broadcast.send(PointsPayload {
-        points,
-        metrics,
-        config: config::POINTS_CONFIG.clone(),
-    });
+/// A sidecar manager set up from the `[sidecar]` config, not started yet
+async fn new_sidecar(state: &Arc<AppState>) -> SidecarManager {
+    let config = state.config.read().await;
+    let mut sidecar = SidecarManager::new();
+    sidecar.set_name(config.sidecar.binary_name());
+    sidecar.set_rpc_timeout(Duration::from_secs(config.sidecar.rpc_timeout_seconds));
+    sidecar
+}
 
-    Ok(())
+struct SidecarStartup {
+    is_authenticated: bool,
+    live_info: sidecar::LiveInfo,
+    initial_subscribers: i64,
+    using_exact_subscribers: bool,
 }
 
-#[tauri::command]
-async fn add_subscriber_points(
+/// Applies superchats pushed by the sidecar until every sender is dropped.
+/// Demos pass no stream and add to the combined metrics directly.
+fn spawn_superchat_handler(
+    state: Arc<AppState>,
+    app: tauri::AppHandle,
+    stream: Option<Arc<StreamSession>>,
+    mut superchat_rx: mpsc::UnboundedReceiver<sidecar::SuperchatEventData>,
+) {
+    tauri::async_runtime::spawn(async move {
+        while let Some(mut superchat) = superchat_rx.recv().await {
+            println!(
+                "Superchat received: {} from {} - {}",
+                superchat.amount, superchat.author, superchat.message
+            );
+
+            check_superchat_clock(&state, &app, &mut superchat).await;
+
+            // Add superchat amount to metrics, converted to the base currency
+            {
+                let amount = {
+                    let config = state.config.read().await;
+                    points::normalize_amount(
+                        superchat.amount,
+                        &superchat.currency,
+                        &config.currency,
+                    )
+                };
+                match &stream {
+                    Some(stream) => {
+                        stream.metrics.write().await.superchat_amount += amount;
+                        sync_combined_metrics(&state).await;
+                    }
+                    None => state.raw_metrics.write().await.superchat_amount += amount,
+                }
+            }
+            {
+                let mut stats = state.superchat_stats.write().await;
+                stats.record(&superchat);
+            }
+            {
+                let mut counters = state.session_counters.write().await;
+                counters.superchat_count += 1;
+            }
+
+            // While paused the amount is only counted; resume_monitoring emits it
+            if *state.is_paused.read().await {
+                continue;
+            }
+
+            // Recalculate and emit points
+            emit_points(&state, &app).await;
+
+            // Also emit superchat event for UI effects
+            let _ = app.emit("superchat", &superchat);
+        }
+    });
+}
+
+/// Applies memberships pushed by the sidecar until every sender is dropped
+fn spawn_membership_handler(
+    state: Arc<AppState>,
+    app: tauri::AppHandle,
+    stream: Arc<StreamSession>,
+    mut membership_rx: mpsc::UnboundedReceiver<sidecar::MembershipEventData>,
+) {
+    tauri::async_runtime::spawn(async move {
+        while let Some(membership) = membership_rx.recv().await {
+            println!(
+                "Membership received: {:?} x{} from {}",
+                membership.kind, membership.count, membership.author
+            );
+
+            // Gifts count every gifted membership; a malformed count still counts as one
+            {
+                let mut metrics = stream.metrics.write().await;
+                metrics.membership_count += membership.count.max(1);
+            }
+            sync_combined_metrics(&state).await;
+
+            // While paused the membership is only counted; resume_monitoring emits it
+            if *state.is_paused.read().await {
+                continue;
+            }
+
+            emit_points(&state, &app).await;
+
+            // Also emit membership event for UI effects
+            let _ = app.emit("membership", &membership);
+        }
+    });
+}
+
+/// Runs the fallible startup steps on a freshly spawned sidecar without touching state.
+/// The sidecar is stopped if any step fails.
+async fn prepare_sidecar(
+    sidecar: &mut SidecarManager,
+    video_id: &str,
+    chat_id: Option<&str>,
+    prewarmed_auth: Option<bool>,
+    allow_exact_count: bool,
+) -> Result<SidecarStartup, String> {
+    let result = run_startup_steps(
+        sidecar,
+        video_id,
+        chat_id,
+        prewarmed_auth,
+        allow_exact_count,
+    )
+    .await;
+    if result.is_err() {
+        let _ = sidecar.stop().await;
+    }
+    result
+}
+
+async fn run_startup_steps(
+    sidecar: &SidecarManager,
+    video_id: &str,
+    chat_id: Option<&str>,
+    prewarmed_auth: Option<bool>,
+    allow_exact_count: bool,
+) -> Result<SidecarStartup, String> {
+    // A prewarmed sidecar already has cookies applied and the client initialized
+    let is_authenticated = match prewarmed_auth {
+        Some(is_authenticated) => is_authenticated,
+        None => sidecar.init().await?,
+    };
+    println!(
+        "YouTube client initialized (authenticated: {})",
+        is_authenticated
+    );
+
+    // Get initial live info
+    let live_info = sidecar
+        .get_live_info(video_id)
+        .await
+        .map_err(|e| match e.code {
+            sidecar::SidecarErrorCode::NotFound => {
+                format!("Video not found ({}): {}", video_id, e)
+            }
+            sidecar::SidecarErrorCode::RateLimited => {
+                format!("YouTube is rate limiting requests, try again later: {}", e)
+            }
+            _ => format!("Failed to get live info ({}): {}", video_id, e),
+        })?;
+    if !live_info.is_live {
+        return Err("The video is not a live stream".into());
+    }
+
+    // Get initial subscriber count - use exact count if authenticated
+    let (initial_subscribers, using_exact_subscribers) = if is_authenticated && allow_exact_count {
+        match sidecar.get_exact_subscriber_count().await {
+            Ok(count) => {
+                println!("Got exact subscriber count: {}", count);
+                (count, true)
+            }
+            Err(e) => {
+                eprintln!("Failed to get exact subscriber count, falling back: {}", e);
+                (
+                    sidecar.get_subscriber_count(&live_info.channel_id).await?,
+                    false,
+                )
+            }
+        }
+    } else {
+        (
+            sidecar.get_subscriber_count(&live_info.channel_id).await?,
+            false,
+        )
+    };
+
+    // Start live chat monitoring (optionally keyed on an explicit chat ID)
+    sidecar
+        .start_live_chat(video_id, chat_id)
+        .await
+        .map_err(|e| format!("Video found but live chat is unavailable: {}", e))?;
+
+    Ok(SidecarStartup {
+        is_authenticated,
+        live_info,
+        initial_subscribers,
+        using_exact_subscribers,
+    })
+}
+
+/// Hands the sidecar the cookies to authenticate with, if any are available
+async fn apply_stored_cookies(
+    sidecar: &SidecarManager,
+    state: &Arc<AppState>,
+    app: &tauri::AppHandle,
+) {
+    // Prefer cookies imported from a browser export, then the YouTube login window
+    let imported_cookies = state.imported_cookies.read().await.clone();
+    if let Some(cookie_str) = imported_cookies {
+        if let Err(e) = sidecar.set_cookies(&cookie_str).await {
+            eprintln!("Failed to set imported cookies: {}", e);
+            set_cookies_failed(state, app).await;
+        }
+    } else if let Some(cookie_str) = login_window_cookies(app)
+        && let Err(e) = sidecar.set_cookies(&cookie_str).await
+    {
+        eprintln!("Failed to set cookies: {}", e);
+        set_cookies_failed(state, app).await;
+    }
+}
+
+/// A sidecar without cookies cannot be authenticated; the next `init` reports the actual state
+async fn set_cookies_failed(state: &Arc<AppState>, app: &tauri::AppHandle) {
+    {
+        let mut auth = state.is_authenticated.write().await;
+        *auth = false;
+    }
+    emit_auth_status(state, app).await;
+}
+
+/// YouTube cookies from the login window as a `Cookie` header value, if logged in
+fn login_window_cookies(app: &tauri::AppHandle) -> Option<String> {
+    let login_window = app.get_webview_window("youtube-login")?;
+    let url: url::Url = "https://www.youtube.com".parse().unwrap();
+    let cookies = login_window.cookies_for_url(url).ok()?;
+    let cookie_str = cookies
+        .iter()
+        .map(|c| format!("{}={}", c.name(), c.value()))
+        .collect::<Vec<_>>()
+        .join("; ");
+    (!cookie_str.is_empty()).then_some(cookie_str)
+}
+
+#[derive(Clone, serde::Serialize)]
+struct AuthStatusPayload {
+    authenticated: bool,
+    /// Whether the subscriber points use the logged-in channel's exact count
+    using_exact_subscribers: bool,
+}
+
+/// Tells the frontend the current authentication state (`auth-status-changed`)
+async fn emit_auth_status(state: &Arc<AppState>, app: &tauri::AppHandle) {
+    let payload = AuthStatusPayload {
+        authenticated: *state.is_authenticated.read().await,
+        using_exact_subscribers: *state.using_exact_subscribers.read().await,
+    };
+    let _ = app.emit("auth-status-changed", &payload);
+}
+
+/// Returns whether the YouTube client is authenticated.
+#[tauri::command]
+async fn get_auth_status(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(*state.is_authenticated.read().await)
+}
+
+/// Re-applies the login window's cookies to the running sidecars without stopping monitoring.
+#[tauri::command]
+async fn reauthenticate(
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<bool, String> {
+    let cookie_str = login_window_cookies(&app)
+        .ok_or("Not logged in: open the YouTube login window and sign in first")?;
+
+    let is_authenticated = reinit_sidecars(&state, &app, Some(&cookie_str))
+        .await?
+        .ok_or("Sidecar not running")?;
+    set_authenticated(&state, &app, is_authenticated).await;
+    Ok(is_authenticated)
+}
+
+/// Hands every running sidecar (each stream's, or the prewarmed one) `cookies`, or the stored
+/// cookies when `None`, and re-runs `init`. Returns the authentication state, or `None` if no
+/// sidecar is running.
+async fn reinit_sidecars(
+    state: &Arc<AppState>,
+    app: &tauri::AppHandle,
+    cookies: Option<&str>,
+) -> Result<Option<bool>, String> {
+    let mut is_authenticated = None;
+    for stream in all_streams(state).await {
+        let sidecar_guard = stream.sidecar.read().await;
+        if let Some(sidecar) = sidecar_guard.as_ref() {
+            is_authenticated = Some(reinit_sidecar(sidecar, state, app, cookies).await?);
+        }
+    }
+    let sidecar_guard = state.prewarmed_sidecar.read().await;
+    if let Some(sidecar) = sidecar_guard.as_ref() {
+        is_authenticated = Some(reinit_sidecar(sidecar, state, app, cookies).await?);
+    }
+    Ok(is_authenticated)
+}
+
+async fn reinit_sidecar(
+    sidecar: &SidecarManager,
+    state: &Arc<AppState>,
+    app: &tauri::AppHandle,
+    cookies: Option<&str>,
+) -> Result<bool, String> {
+    match cookies {
+        Some(cookies) => {
+            if let Err(e) = sidecar.set_cookies(cookies).await {
+                set_cookies_failed(state, app).await;
+                return Err(e.into());
+            }
+        }
+        None => apply_stored_cookies(sidecar, state, app).await,
+    }
+    Ok(sidecar.init().await?)
+}
+
+#[derive(Clone, serde::Serialize)]
+struct SidecarReloadPayload {
+    /// `starting`, `initializing`, `swapping`, `resuming`, `done` or `failed`
+    stage: &'static str,
+    error: Option<String>,
+}
+
+fn emit_reload_stage(app: &tauri::AppHandle, stage: &'static str) {
+    let _ = app.emit(
+        "sidecar-reload",
+        &SidecarReloadPayload { stage, error: None },
+    );
+}
+
+/// Swaps the running sidecar for a freshly spawned one (e.g. after an app update) without
+/// stopping monitoring; points and metrics are kept. Progress is sent as `sidecar-reload` events.
+/// Reloads the sidecar of `video_id`, or of every stream when it is omitted.
+/// Returns whether the new YouTube client is authenticated.
+#[tauri::command]
+async fn reload_sidecar(
+    video_id: Option<String>,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<bool, String> {
+    let streams = match video_id {
+        Some(video_id) => vec![find_stream(&state, Some(&video_id)).await?],
+        None => all_streams(&state).await,
+    };
+    let mut result = Err("Not monitoring".to_string());
+    for stream in streams {
+        result = restart_sidecar(&state, &app, &stream, "Reload requested").await;
+        if result.is_err() {
+            break;
+        }
+    }
+    let _ = app.emit(
+        "sidecar-reload",
+        &SidecarReloadPayload {
+            stage: if result.is_ok() { "done" } else { "failed" },
+            error: result.as_ref().err().cloned(),
+        },
+    );
+    result
+}
+
+#[derive(Clone, serde::Serialize)]
+struct SidecarRestartedPayload {
+    reason: String,
+    attempt: u32,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct SidecarFailedPayload {
+    reason: String,
+    attempts: u32,
+    error: String,
+}
+
+/// Restarts the sidecar when it exits on its own during monitoring, retrying with backoff.
+/// Ends once the sidecar it watches is replaced or stopped and its sender is dropped.
+fn spawn_sidecar_supervisor(
+    state: Arc<AppState>,
+    app: tauri::AppHandle,
+    stream: Arc<StreamSession>,
+    mut termination_rx: mpsc::UnboundedReceiver<String>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let Some(reason) = termination_rx.recv().await else {
+            return;
+        };
+        eprintln!("Sidecar terminated unexpectedly: {}", reason);
+
+        let mut last_error = String::new();
+        for attempt in 1..=config::SIDECAR_RESTART_MAX_ATTEMPTS {
+            let delay = config::SIDECAR_RESTART_BASE_DELAY_SECONDS << (attempt - 1);
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+
+            if !*state.is_monitoring.read().await || !is_active_stream(&state, &stream).await {
+                return;
+            }
+            match restart_sidecar(&state, &app, &stream, &format!("Terminated: {}", reason)).await {
+                Ok(_) => {
+                    let _ = app.emit(
+                        "sidecar-restarted",
+                        SidecarRestartedPayload {
+                            reason: reason.clone(),
+                            attempt,
+                        },
+                    );
+                    return;
+                }
+                Err(e) => {
+                    eprintln!("Sidecar restart attempt {} failed: {}", attempt, e);
+                    last_error = e;
+                }
+            }
+        }
+
+        let _ = app.emit(
+            "sidecar-failed",
+            SidecarFailedPayload {
+                reason,
+                attempts: config::SIDECAR_RESTART_MAX_ATTEMPTS,
+                error: last_error,
+            },
+        );
+    });
+}
+
+/// Pings the sidecar while the session lasts and sends `sidecar-status` when its health changes
+fn spawn_sidecar_health_check(state: Arc<AppState>, app: tauri::AppHandle, start_generation: u64) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(config::SIDECAR_PING_INTERVAL_SECONDS));
+
+        loop {
+            ticker.tick().await;
+
+            if !*state.is_monitoring.read().await
+                || state.start_generation.load(Ordering::SeqCst) != start_generation
+            {
+                break;
+            }
+
+            // With several streams, the least healthy sidecar decides the status
+            let mut status = SidecarStatus::Stopped;
+            for stream in all_streams(&state).await {
+                let stream_status = ping_stream(&stream).await;
+                status = match (status, stream_status) {
+                    (SidecarStatus::Unresponsive, _) | (_, SidecarStatus::Unresponsive) => {
+                        SidecarStatus::Unresponsive
+                    }
+                    (SidecarStatus::Running, _) | (_, SidecarStatus::Running) => {
+                        SidecarStatus::Running
+                    }
+                    _ => SidecarStatus::Stopped,
+                };
+            }
+            set_sidecar_status(&state, &app, status).await;
+        }
+
+        // A newer session runs its own check; only report the stop if nothing took over
+        if !*state.is_monitoring.read().await {
+            set_sidecar_status(&state, &app, SidecarStatus::Stopped).await;
+        }
+    });
+}
+
+async fn ping_stream(stream: &StreamSession) -> SidecarStatus {
+    let sidecar_guard = stream.sidecar.read().await;
+    match sidecar_guard.as_ref() {
+        None => SidecarStatus::Stopped,
+        Some(sidecar) => match sidecar.ping().await {
+            Ok(()) => SidecarStatus::Running,
+            Err(_) if !sidecar.is_running().await => SidecarStatus::Stopped,
+            Err(e) => {
+                eprintln!("Sidecar for {} did not answer ping: {}", stream.video_id, e);
+                SidecarStatus::Unresponsive
+            }
+        },
+    }
+}
+
+async fn set_sidecar_status(state: &Arc<AppState>, app: &tauri::AppHandle, status: SidecarStatus) {
+    let mut current = state.sidecar_status.write().await;
+    if *current != status {
+        *current = status;
+        let _ = app.emit("sidecar-status", status);
+    }
+}
+
+/// Returns whether the sidecar is stopped, running, or running but not answering pings.
+#[tauri::command]
+async fn get_sidecar_status(state: State<'_, Arc<AppState>>) -> Result<SidecarStatus, String> {
+    let mut is_running = false;
+    for stream in all_streams(&state).await {
+        if let Some(sidecar) = stream.sidecar.read().await.as_ref() {
+            is_running |= sidecar.is_running().await;
+        }
+    }
+    if !is_running {
+        return Ok(SidecarStatus::Stopped);
+    }
+    // Running until a ping says otherwise, so a fresh session does not wait for the first ping
+    Ok(match *state.sidecar_status.read().await {
+        SidecarStatus::Unresponsive => SidecarStatus::Unresponsive,
+        _ => SidecarStatus::Running,
+    })
+}
+
+/// Starts a new sidecar for `stream`, replaces the running one with it, and resumes
+/// live chat. The old sidecar keeps running until the new one is initialized.
+async fn restart_sidecar(
+    state: &Arc<AppState>,
+    app: &tauri::AppHandle,
+    stream: &Arc<StreamSession>,
+    reason: &str,
+) -> Result<bool, String> {
+    if !*state.is_monitoring.read().await || !is_active_stream(state, stream).await {
+        return Err("Not monitoring".into());
+    }
+
+    emit_reload_stage(app, "starting");
+    let (superchat_tx, superchat_rx) = mpsc::unbounded_channel();
+    let (membership_tx, membership_rx) = mpsc::unbounded_channel();
+    let (termination_tx, termination_rx) = mpsc::unbounded_channel();
+    let mut sidecar = new_sidecar(state).await;
+    sidecar.set_superchat_handler(superchat_tx).await;
+    sidecar.set_membership_handler(membership_tx).await;
+    sidecar.set_termination_handler(termination_tx).await;
+    sidecar.start(app).await?;
+
+    emit_reload_stage(app, "initializing");
+    apply_stored_cookies(&sidecar, state, app).await;
+    let is_authenticated = match sidecar.init().await {
+        Ok(is_authenticated) => is_authenticated,
+        Err(e) => {
+            let _ = sidecar.stop().await;
+            return Err(format!("New sidecar failed to initialize: {}", e));
+        }
+    };
+
+    emit_reload_stage(app, "swapping");
+    let previous = {
+        let monitoring = state.is_monitoring.read().await;
+        if !*monitoring || !is_active_stream(state, stream).await {
+            drop(monitoring);
+            let _ = sidecar.stop().await;
+            return Err("Monitoring stopped during the reload".into());
+        }
+        stream.sidecar.write().await.replace(sidecar)
+    };
+    // Stopping the old sidecar also drops its event senders, ending its handlers
+    if let Some(mut previous) = previous {
+        let _ = previous.stop().await;
+    }
+    spawn_superchat_handler(
+        state.clone(),
+        app.clone(),
+        Some(stream.clone()),
+        superchat_rx,
+    );
+    spawn_membership_handler(state.clone(), app.clone(), stream.clone(), membership_rx);
+    spawn_sidecar_supervisor(state.clone(), app.clone(), stream.clone(), termination_rx);
+
+    emit_reload_stage(app, "resuming");
+    {
+        let sidecar_guard = stream.sidecar.read().await;
+        let sidecar = sidecar_guard.as_ref().ok_or("Sidecar not running")?;
+        sidecar
+            .start_live_chat(&stream.video_id, stream.chat_id.as_deref())
+            .await
+            .map_err(|e| format!("Failed to resume live chat: {}", e))?;
+    }
+
+    {
+        let mut restart_count = state.sidecar_restart_count.write().await;
+        *restart_count += 1;
+    }
+    {
+        let mut last_reason = state.last_restart_reason.write().await;
+        *last_reason = Some(reason.to_string());
+    }
+    set_authenticated(state, app, is_authenticated).await;
+    println!("Sidecar restarted for {}: {}", stream.video_id, reason);
+    Ok(is_authenticated)
+}
+
+/// Starts and initializes the sidecar ahead of time so `start_monitoring` can skip that step.
+/// Returns whether the YouTube client is authenticated.
+#[tauri::command]
+async fn prewarm_sidecar(
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<bool, String> {
+    if *state.is_monitoring.read().await {
+        return Err("Already monitoring".into());
+    }
+    {
+        let sidecar_guard = state.prewarmed_sidecar.read().await;
+        if let Some(sidecar) = sidecar_guard.as_ref()
+            && sidecar.is_running().await
+        {
+            return Ok(*state.is_authenticated.read().await);
+        }
+    }
+
+    let mut sidecar = new_sidecar(&state).await;
+    sidecar.start(&app).await?;
+    apply_stored_cookies(&sidecar, &state, &app).await;
+    let is_authenticated = match sidecar.init().await {
+        Ok(is_authenticated) => is_authenticated,
+        Err(e) => {
+            let _ = sidecar.stop().await;
+            return Err(e.into());
+        }
+    };
+
+    {
+        // Same lock order as start_monitoring
+        let monitoring = state.is_monitoring.read().await;
+        if *monitoring {
+            drop(monitoring);
+            let _ = sidecar.stop().await;
+            return Err("Already monitoring".into());
+        }
+        let mut sidecar_guard = state.prewarmed_sidecar.write().await;
+        if let Some(mut previous) = sidecar_guard.replace(sidecar) {
+            let _ = previous.stop().await;
+        }
+    }
+    println!("Sidecar prewarmed");
+    set_authenticated(&state, &app, is_authenticated).await;
+    Ok(is_authenticated)
+}
+
+/// Takes the sidecar left by prewarm_sidecar, with its authentication state, if it is still running
+async fn take_prewarmed_sidecar(state: &Arc<AppState>) -> Option<(SidecarManager, bool)> {
+    let mut sidecar = state.prewarmed_sidecar.write().await.take()?;
+    if sidecar.is_running().await {
+        return Some((sidecar, *state.is_authenticated.read().await));
+    }
+    eprintln!("Prewarmed sidecar is no longer running, starting a new one");
+    let _ = sidecar.stop().await;
+    None
+}
+
+/// Re-applies the stored cookies and re-runs `init` on the running sidecars.
+#[tauri::command]
+async fn refresh_sidecar(
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<bool, String> {
+    let is_authenticated = reinit_sidecars(&state, &app, None)
+        .await?
+        .ok_or("Sidecar not running")?;
+    set_authenticated(&state, &app, is_authenticated).await;
+    Ok(is_authenticated)
+}
+
+async fn set_authenticated(state: &Arc<AppState>, app: &tauri::AppHandle, is_authenticated: bool) {
+    println!(
+        "YouTube client re-initialized (authenticated: {})",
+        is_authenticated
+    );
+    {
+        let mut auth = state.is_authenticated.write().await;
+        *auth = is_authenticated;
+    }
+    emit_auth_status(state, app).await;
+}
+
+/// Validates user input without starting monitoring; returns the extracted video ID.
+#[tauri::command]
+fn parse_video_id(input: String) -> Result<String, String> {
+    sidecar::extract_video_id(&input)
+}
+
+#[derive(Clone, serde::Serialize)]
+struct BaselineCorrectedPayload {
+    previous: i64,
+    corrected: i64,
+}
+
+/// Current subscriber count - the exact count when available, otherwise the public one -
+/// and whether it is the exact count
+async fn fetch_subscriber_count(
+    sidecar: &SidecarManager,
+    use_exact_count: bool,
+    channel_id: &str,
+) -> Result<(i64, bool), String> {
+    if use_exact_count {
+        match sidecar.get_exact_subscriber_count().await {
+            Ok(count) => return Ok((count, true)),
+            Err(e) => eprintln!("Failed to get exact subscriber count, falling back: {}", e),
+        }
+    }
+    Ok((sidecar.get_subscriber_count(channel_id).await?, false))
+}
+
+/// Polls the stream's sidecar and updates its metrics and the combined ones.
+/// Returns whether the stream is still live.
+async fn update_metrics(
+    state: &Arc<AppState>,
+    app: &tauri::AppHandle,
+    stream: &StreamSession,
+) -> Result<bool, String> {
+    let channel_id = stream.channel_id.read().await.clone();
+    let use_exact_count = uses_exact_count(state, stream).await;
+
+    let sidecar_guard = stream.sidecar.read().await;
+    let sidecar = sidecar_guard.as_ref().ok_or("Sidecar not running")?;
+
+    // Get live info
+    let live_info = sidecar.get_live_info(&stream.video_id).await?;
+    {
+        let mut last_live_info = stream.live_info.write().await;
+        *last_live_info = live_info.clone();
+    }
+
+    let (current_subscribers, exact) =
+        fetch_subscriber_count(sidecar, use_exact_count, &channel_id).await?;
+    drop(sidecar_guard);
+    if stream.allow_exact_count {
+        let changed = {
+            let mut using_exact = state.using_exact_subscribers.write().await;
+            std::mem::replace(&mut *using_exact, exact) != exact
+        };
+        if changed {
+            emit_auth_status(state, app).await;
+        }
+    }
+    if use_exact_count && !exact {
+        emit_monitoring_error(
+            app,
+            "subscriber_count",
+            "Exact subscriber count unavailable, using the public count",
+        );
+    }
+
+    let metrics_config = state.config.read().await.metrics.clone();
+
+    // Update metrics
+    let mut accepted_viewers = None;
+    {
+        let mut metrics = stream.metrics.write().await;
+
+        // Correct a bad starting baseline (hidden or transient zero count) once per session
+        let mut corrected = stream.baseline_corrected.write().await;
+        if metrics_config.rebaseline_subscribers
+            && !*corrected
+            && metrics.has_implausible_baseline(current_subscribers)
+        {
+            *corrected = true;
+            let payload = BaselineCorrectedPayload {
+                previous: metrics.initial_subscribers,
+                corrected: current_subscribers,
+            };
+            println!(
+                "Subscriber baseline corrected: {} -> {}",
+                payload.previous, payload.corrected
+            );
+            metrics.initial_subscribers = current_subscribers;
+            let _ = app.emit("baseline-corrected", &payload);
+        }
+
+        // Fields missing from a partial LiveInfo keep their last known value
+        if let Some(viewers) = live_info.concurrent_viewers {
+            // Hold the last good value over a single sudden drop; a second low reading is accepted
+            let mut dip_pending = stream.viewer_dip_pending.write().await;
+            if metrics.update_viewers(
+                viewers,
+                metrics_config.suppress_viewer_dips,
+                &mut dip_pending,
+            ) {
+                accepted_viewers = Some(viewers);
+            } else {
+                println!(
+                    "Ignoring concurrent viewer drop {} -> {} until confirmed",
+                    metrics.concurrent_viewers, viewers
+                );
+            }
+        }
+        metrics.update_likes(live_info.like_count, metrics_config.clamp_negative_likes);
+        // Hidden likes come back as null; tell the UI once why the count stopped moving
+        if live_info.like_count.is_none() {
+            let mut notified = stream.likes_unavailable_notified.write().await;
+            if !*notified {
+                *notified = true;
+                let _ = app.emit("likes-unavailable", &stream.video_id);
+            }
+        }
+        if let Some(extra_metrics) = live_info.extra_metrics {
+            metrics.extra_metrics = extra_metrics;
+        }
+        metrics.current_subscribers = current_subscribers;
+    }
+    sync_combined_metrics(state).await;
+    // Viewer stats cover every stream together
+    if accepted_viewers.is_some() {
+        let viewers = state.raw_metrics.read().await.concurrent_viewers;
+        let mut counters = state.session_counters.write().await;
+        counters.record_viewers(viewers);
+    }
+    {
+        let mut last_update = state.last_metrics_update.write().await;
+        *last_update = Some(Instant::now());
+    }
+
+    Ok(live_info.is_live)
+}
+
+/// Whether the stream's subscribers come from the exact (authenticated) count.
+/// Exact counts belong to the logged-in channel, so skip them for an overridden channel.
+async fn uses_exact_count(state: &Arc<AppState>, stream: &StreamSession) -> bool {
+    stream.allow_exact_count
+        && *state.is_authenticated.read().await
+        && !*stream.channel_overridden.read().await
+}
+
+#[derive(Clone, serde::Serialize)]
+struct PollBenchmark {
+    /// `getLiveInfo` latency when called on its own
+    live_info_ms: u64,
+    /// Subscriber count latency when called on its own
+    subscriber_count_ms: u64,
+    /// Both calls one after another, as the polling task does today
+    sequential_total_ms: u64,
+    /// Both calls issued concurrently
+    concurrent_total_ms: u64,
+    polling_interval_ms: u64,
+}
+
+/// Times the sidecar calls a poll makes, without updating any state.
+#[tauri::command]
+async fn benchmark_poll(state: State<'_, Arc<AppState>>) -> Result<PollBenchmark, String> {
+    let stream = find_stream(&state, None).await?;
+    let video_id = stream.video_id.clone();
+    let channel_id = stream.channel_id.read().await.clone();
+    let use_exact_count = uses_exact_count(&state, &stream).await;
+
+    let sidecar_guard = stream.sidecar.read().await;
+    let sidecar = sidecar_guard.as_ref().ok_or("Sidecar not running")?;
+
+    let started = Instant::now();
+    sidecar.get_live_info(&video_id).await?;
+    let live_info_elapsed = started.elapsed();
+
+    let started = Instant::now();
+    fetch_subscriber_count(sidecar, use_exact_count, &channel_id).await?;
+    let subscriber_count_elapsed = started.elapsed();
+
+    let started = Instant::now();
+    let (live_info, subscribers) = tokio::join!(
+        sidecar.get_live_info(&video_id),
+        fetch_subscriber_count(sidecar, use_exact_count, &channel_id)
+    );
+    live_info?;
+    subscribers?;
+    let concurrent_elapsed = started.elapsed();
+
+    Ok(PollBenchmark {
+        live_info_ms: live_info_elapsed.as_millis() as u64,
+        subscriber_count_ms: subscriber_count_elapsed.as_millis() as u64,
+        sequential_total_ms: (live_info_elapsed + subscriber_count_elapsed).as_millis() as u64,
+        concurrent_total_ms: concurrent_elapsed.as_millis() as u64,
+        polling_interval_ms: state.config.read().await.polling.min_interval_seconds * 1000,
+    })
+}
+
+/// Drops a stream that has ended. Once the last stream has ended, stops monitoring and
+/// schedules the optional auto-reset.
+async fn handle_stream_end(state: &Arc<AppState>, app: &tauri::AppHandle, stream: &StreamSession) {
+    // Other streams of the session keep going
+    if state.streams.read().await.len() > 1 {
+        println!("Stream ended: {}", stream.video_id);
+        if let Err(e) = stop_stream(state, app, &stream.video_id).await {
+            eprintln!("Failed to stop stream after it ended: {}", e);
+            emit_monitoring_error(app, "stream_end", &e);
+        }
+        return;
+    }
+
+    println!("Stream ended, stopping monitoring");
+    let _ = app.emit("stream-ended", ());
+
+    if let Err(e) = shutdown_monitoring(state).await {
+        eprintln!("Failed to stop monitoring after stream end: {}", e);
+        emit_monitoring_error(app, "stream_end", &e);
+    }
+
+    let Some(delay) = state
+        .config
+        .read()
+        .await
+        .session
+        .auto_reset_after_end_seconds
+    else {
+        return;
+    };
+
+    // Cancelled if monitoring starts again before the delay elapses
+    let generation = state.start_generation.load(Ordering::SeqCst);
+    let _ = app.emit("auto-reset-scheduled", delay);
+    println!("Points will be reset in {} seconds", delay);
+
+    let state = state.clone();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(delay)).await;
+
+        if *state.is_monitoring.read().await
+            || state.start_generation.load(Ordering::SeqCst) != generation
+        {
+            println!("Auto-reset cancelled");
+            let _ = app.emit("auto-reset-cancelled", ());
+            return;
+        }
+
+        clear_points(&state).await;
+        let points = state.points.read().await.clone();
+        let metrics = state.raw_metrics.read().await.clone();
+        send_points_update(&state, &app, points, metrics).await;
+        println!("Points auto-reset after stream end");
+        let _ = app.emit("auto-reset", ());
+    });
+}
+
+/// Recalculates the points from the metrics and the hand-entered counts (subscribers, manual,
+/// visitor) in the stored points, and sends them out. Every point update goes through here
+/// except a reset, which stores zeroed points through `clear_points`; the add_*_points
+/// commands only change their own count and recalculate and publish the same way.
+pub(crate) async fn emit_points(state: &Arc<AppState>, app: &tauri::AppHandle) {
+    let (points, metrics, capped, new_milestones) = recalculate_points(state).await;
+    publish_points(state, app, points, metrics, capped, new_milestones).await;
+}
+
+/// Sends out points that were just recalculated, announcing the milestones and cap they reached
+async fn publish_points(
+    state: &Arc<AppState>,
+    app: &tauri::AppHandle,
+    points: points::PointState,
+    metrics: points::RawMetrics,
+    capped: bool,
+    new_milestones: Vec<config::Milestone>,
+) {
+    for milestone in &new_milestones {
+        println!(
+            "Superchat milestone reached: {} yen (+{})",
+            milestone.threshold_yen, milestone.bonus_points
+        );
+        let _ = app.emit("milestone-reached", milestone);
+    }
+
+    if capped {
+        let mut cap_reached = state.cap_reached.write().await;
+        if !*cap_reached {
+            *cap_reached = true;
+            let _ = app.emit("cap-reached", points.total);
+            send_web_event(state, WebEvent::CapReached(points.total)).await;
+        }
+    }
+
+    record_snapshot(state, &points, &metrics).await;
+    send_points_update(state, app, points, metrics).await;
+
+    // Keep the saved session current so a crash does not lose the points.
+    // Only real sessions have a video ID; demos and idle updates are not saved.
+    if state.monitoring_video_id.read().await.is_some()
+        && let Err(e) = persist_session(state).await
+    {
+        eprintln!("Failed to save session: {}", e);
+        emit_monitoring_error(app, "save_session", &e);
+    }
+}
+
+/// Recalculates the points and stores them. Returns the points, the metrics they came from,
+/// whether the total cap applied, and the milestones reached by this update.
+async fn recalculate_points(
+    state: &Arc<AppState>,
+) -> (
+    points::PointState,
+    points::RawMetrics,
+    bool,
+    Vec<config::Milestone>,
+) {
+    let points_config = state.config.read().await.points.clone();
+    let metrics = state.raw_metrics.read().await;
+
+    // Check concurrent bonus (50人超えたら1回だけ1000円)
+    let mut bonus_given = state.concurrent_bonus_given.write().await;
+    if metrics.concurrent_viewers > config::CONCURRENT_BONUS_THRESHOLD && !*bonus_given {
+        *bonus_given = true;
+    }
+
+    // Superchat milestones award their bonus once, even when one superchat passes several
+    let mut milestones_reached = state.milestones_reached.write().await;
+    let new_milestones =
+        points_config.new_milestones(metrics.superchat_amount, &milestones_reached);
+    milestones_reached.extend(new_milestones.iter().cloned());
+    let milestone_bonus = milestones_reached
+        .iter()
+        .map(|milestone| milestone.bonus_points)
+        .sum();
+    drop(milestones_reached);
+
+    // The lock is held until the result is stored, so an add_*_points call in between
+    // cannot be overwritten with the old count
+    let mut current_points = state.points.write().await;
+    let inputs = points::PointInputs {
+        concurrent_bonus_given: *bonus_given,
+        milestone_bonus,
+        ..points::PointInputs::from_counts(&current_points)
+    };
+    let mut calculated = points::PointState::calculate(&metrics, &inputs, &points_config);
+
+    // Only the total is clamped; the per-source breakdown stays accurate
+    let capped = calculated.apply_cap(points_config.total_cap);
+    *current_points = calculated.clone();
+
+    (calculated, metrics.clone(), capped, new_milestones)
+}
+
+/// Points counted toward the goal
+fn goal_points(
+    points: &points::PointState,
+    points_config: &config::PointsConfig,
+    goal_counts_manual: bool,
+    goal: i64,
+) -> i64 {
+    let goal_points = points.goal_points(points_config, goal_counts_manual);
+    // Hitting the cap completes the goal even if the cap is set below it
+    if points_config
+        .total_cap
+        .is_some_and(|cap| points.total >= cap)
+    {
+        return goal_points.max(goal);
+    }
+    goal_points
+}
+
+/// Emit a points update to the main window and broadcast it to web clients
+async fn send_points_update(
+    state: &Arc<AppState>,
+    app: &tauri::AppHandle,
+    points: points::PointState,
+    metrics: points::RawMetrics,
+) {
+    let (payload, goals) = points_payload(state, points, metrics).await;
+    announce_goals_reached(state, app, &goals, payload.goals_reached).await;
+    let _ = app.emit("points-update", &payload);
+
+    // Broadcast to web clients
+    broadcast_points(state, payload).await;
+}
+
+/// Builds the payload sent to the main window and web clients, along with the goal list
+async fn points_payload(
+    state: &Arc<AppState>,
+    points: points::PointState,
+    metrics: points::RawMetrics,
+) -> (PointsPayload, Vec<i64>) {
+    let (points_config, goal_counts_manual, progress_metric, goals, show_time_to_goal) = {
+        let config = state.config.read().await;
+        (
+            config.points.clone(),
+            config.overlay.goal_counts_manual,
+            config.overlay.progress_metric,
+            config.overlay.goal_list(),
+            config.overlay.show_time_to_goal,
+        )
+    };
+    // Manual additions while idle are sent too; metrics only go stale during monitoring
+    let is_monitoring = *state.is_monitoring.read().await;
+    let is_paused = is_monitoring && *state.is_paused.read().await;
+    // Metrics are expected to age while paused
+    let is_stale = is_monitoring && !is_paused && is_data_stale(state).await;
+    let using_exact_subscribers = *state.using_exact_subscribers.read().await;
+    // The cap completes every goal, so it is measured against the last one
+    let final_goal = goals.last().copied().unwrap_or_default();
+    let goal_points = goal_points(&points, &points_config, goal_counts_manual, final_goal);
+    let progress_value = progress_metric.value(goal_points, &metrics);
+    let goal_status = points::GoalStatus::compute(&goals, progress_value);
+    let goal = goal_status.active;
+    let time_to_goal = if show_time_to_goal && progress_metric == config::ProgressMetric::Total {
+        Some(points::GoalEstimate::compute(
+            goal_points,
+            goal,
+            monitoring_seconds(state).await,
+        ))
+    } else {
+        None
+    };
+
+    let payload = PointsPayload {
+        points,
+        metrics,
+        config: points_config,
+        is_monitoring,
+        is_stale,
+        is_paused,
+        using_exact_subscribers,
+        goal_points,
+        progress_metric,
+        progress_value,
+        goal,
+        next_goal: goal_status.next,
+        goals_reached: goal_status.reached,
+        goal_count: goal_status.count,
+        time_to_goal,
+    };
+    (payload, goals)
+}
+
+#[derive(Clone, serde::Serialize)]
+struct GoalReachedPayload {
+    /// 1-based position of the goal in the list
+    number: usize,
+    goal: i64,
+    next_goal: Option<i64>,
+}
+
+/// Emits `goal-reached` for each goal passed since the last update.
+/// The count follows the progress down too, so goals reached again after a reset are re-announced.
+async fn announce_goals_reached(
+    state: &Arc<AppState>,
+    app: &tauri::AppHandle,
+    goals: &[i64],
+    reached: usize,
+) {
+    let previous = std::mem::replace(&mut *state.goals_reached.write().await, reached);
+    for index in previous..reached {
+        let _ = app.emit(
+            "goal-reached",
+            GoalReachedPayload {
+                number: index + 1,
+                goal: goals[index],
+                next_goal: goals.get(index + 1).copied(),
+            },
+        );
+    }
+}
+
+/// Sends a payload to web clients, holding back bursts so the overlay can animate smoothly.
+/// The last payload of a burst is always delivered once the interval has passed.
+async fn broadcast_points(state: &Arc<AppState>, payload: PointsPayload) {
+    // `GET /points` serves the latest payload even while a broadcast is held back
+    {
+        let mut latest = state.latest_points.write().await;
+        *latest = Some(payload.clone());
+    }
+    let min_interval =
+        Duration::from_millis(state.config.read().await.server.min_broadcast_interval_ms);
+    let mut throttle = state.broadcast_throttle.write().await;
+    let wait = throttle
+        .last_sent
+        .map(|sent_at| min_interval.saturating_sub(sent_at.elapsed()))
+        .unwrap_or_default();
+
+    if wait.is_zero() {
+        throttle.last_sent = Some(Instant::now());
+        throttle.pending = None;
+        send_web_event(state, WebEvent::Points(Box::new(payload))).await;
+        return;
+    }
+
+    // A flush is already scheduled whenever something is pending; it picks up the newest payload
+    let flush_scheduled = throttle.pending.is_some();
+    throttle.pending = Some(payload);
+    if flush_scheduled {
+        return;
+    }
+
+    let state = state.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(wait).await;
+        let mut throttle = state.broadcast_throttle.write().await;
+        if let Some(payload) = throttle.pending.take() {
+            throttle.last_sent = Some(Instant::now());
+            send_web_event(&state, WebEvent::Points(Box::new(payload))).await;
+        }
+    });
+}
+
+/// Sends an event to web clients and records whether anyone received it
+async fn send_web_event(state: &Arc<AppState>, event: WebEvent) {
+    let result = state.web_broadcast.send(event);
+    let mut stats = state.broadcast_stats.write().await;
+    stats.last_attempt_at = Some(unix_now());
+    match result {
+        Ok(receivers) => {
+            stats.total_sent += 1;
+            stats.last_receiver_count = receivers;
+        }
+        // Sending only fails when no overlay is connected
+        Err(_) => {
+            stats.total_dropped += 1;
+            stats.last_receiver_count = 0;
+        }
+    }
+}
+
+/// Returns web broadcast telemetry, showing whether overlays are actually receiving updates.
+#[tauri::command]
+async fn get_broadcast_stats(state: State<'_, Arc<AppState>>) -> Result<BroadcastStats, String> {
+    Ok(state.broadcast_stats.read().await.clone())
+}
+
+/// Time since the last successful metrics update, if any
+async fn data_age(state: &Arc<AppState>) -> Option<Duration> {
+    let last_update = state.last_metrics_update.read().await;
+    last_update.map(|instant| instant.elapsed())
+}
+
+/// Whether metrics have not been updated for several polling intervals
+///
+/// Measured against the longest interval so a backed-off poll is not reported as stale.
+async fn is_data_stale(state: &Arc<AppState>) -> bool {
+    let max_interval = state.config.read().await.polling.max_interval_seconds;
+    data_age(state)
+        .await
+        .is_some_and(|age| age.as_secs() > max_interval * config::STALE_AFTER_POLLS)
+}
+
+/// Returns seconds since the last successful metrics update, or `None` before the first one.
+#[tauri::command]
+async fn get_data_freshness(state: State<'_, Arc<AppState>>) -> Result<Option<u64>, String> {
+    Ok(data_age(&state).await.map(|age| age.as_secs()))
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum SessionHealth {
+    Stopped,
+    Healthy,
+    Stale,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct SessionDiagnostics {
+    uptime_seconds: Option<u64>,
+    restart_count: u32,
+    last_restart_reason: Option<String>,
+    health: SessionHealth,
+}
+
+#[tauri::command]
+async fn get_session_diagnostics(
+    state: State<'_, Arc<AppState>>,
+) -> Result<SessionDiagnostics, String> {
+    let is_monitoring = *state.is_monitoring.read().await;
+    let is_stale = is_data_stale(&state).await;
+    let health = if !is_monitoring {
+        SessionHealth::Stopped
+    } else if is_stale {
+        SessionHealth::Stale
+    } else {
+        SessionHealth::Healthy
+    };
+
+    Ok(SessionDiagnostics {
+        uptime_seconds: state
+            .monitoring_started_at
+            .read()
+            .await
+            .map(|started_at| started_at.elapsed().as_secs()),
+        restart_count: *state.sidecar_restart_count.read().await,
+        last_restart_reason: state.last_restart_reason.read().await.clone(),
+        health,
+    })
+}
+
+/// Tracks a different channel's subscribers (e.g. for collab streams) and rebaselines to it.
+/// Applies to `video_id`, or to the first stream when it is omitted.
+#[tauri::command]
+async fn set_monitoring_channel(
+    channel_id: String,
+    video_id: Option<String>,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let channel_id = channel_id.trim().to_string();
+    if channel_id.is_empty() {
+        return Err("Channel ID is empty".into());
+    }
+    if !*state.is_monitoring.read().await {
+        return Err("Not monitoring".into());
+    }
+    let stream = find_stream(&state, video_id.as_deref()).await?;
+
+    let subscribers = {
+        let sidecar_guard = stream.sidecar.read().await;
+        let sidecar = sidecar_guard.as_ref().ok_or("Sidecar not running")?;
+        sidecar.get_subscriber_count(&channel_id).await?
+    };
+
+    {
+        let mut cid = stream.channel_id.write().await;
+        *cid = channel_id.clone();
+    }
+    {
+        let mut overridden = stream.channel_overridden.write().await;
+        *overridden = true;
+    }
+    if stream.allow_exact_count {
+        {
+            let mut using_exact = state.using_exact_subscribers.write().await;
+            *using_exact = false;
+        }
+        emit_auth_status(&state, &app).await;
+    }
+    {
+        let mut metrics = stream.metrics.write().await;
+        metrics.initial_subscribers = subscribers;
+        metrics.current_subscribers = subscribers;
+    }
+    // The baseline was set explicitly, so don't let the heuristic replace it
+    {
+        let mut corrected = stream.baseline_corrected.write().await;
+        *corrected = true;
+    }
+    sync_combined_metrics(&state).await;
+
+    println!(
+        "Monitoring channel set to {} ({} subscribers)",
+        channel_id, subscribers
+    );
+    emit_points(&state, &app).await;
+    Ok(())
+}
+
+/// Stops monitoring `video_id`, or every stream when it is omitted.
+/// Stopping the last stream ends the session.
+#[tauri::command]
+async fn stop_monitoring(
+    video_id: Option<String>,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    match video_id {
+        Some(video_id) => stop_stream(&state, &app, &video_id).await,
+        None => shutdown_monitoring(&state).await,
+    }
+}
+
+/// Removes one stream from the session; its points no longer count toward the total
+async fn stop_stream(
+    state: &Arc<AppState>,
+    app: &tauri::AppHandle,
+    video_id: &str,
+) -> Result<(), String> {
+    let stream = {
+        let mut streams = state.streams.write().await;
+        if !streams.contains_key(video_id) {
+            return Err(format!("Not monitoring {}", video_id));
+        }
+        if streams.len() == 1 {
+            drop(streams);
+            return shutdown_monitoring(state).await;
+        }
+        let stream = streams.remove(video_id);
+        // Keep the session keyed on a stream that is still running
+        let mut primary = state.monitoring_video_id.write().await;
+        if primary.as_deref() == Some(video_id) {
+            *primary = streams.keys().min().cloned();
+        }
+        stream
+    };
+
+    if let Some(stream) = stream
+        && let Some(mut sidecar) = stream.sidecar.write().await.take()
+    {
+        sidecar.stop().await?;
+    }
+    sync_combined_metrics(state).await;
+    emit_points(state, app).await;
+    let _ = app.emit("streams-changed", list_streams(state).await);
+    println!("Stopped monitoring {}", video_id);
+    Ok(())
+}
+
+/// Stops monitoring by killing the sidecar without the graceful `stopLiveChat` step,
+/// for recovering from a sidecar that no longer responds.
+#[tauri::command]
+async fn force_stop(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    end_session(&state, true).await
+}
+
+/// Puts point accumulation on hold while keeping the sidecar and live chat running.
+/// Polls are skipped and superchats are only counted until `resume_monitoring`.
+#[tauri::command]
+async fn pause_monitoring(
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    set_paused(&state, true).await?;
+    println!("Monitoring paused");
+    let _ = app.emit("monitoring-paused", true);
+    send_web_event(&state, WebEvent::Paused(true)).await;
+    Ok(())
+}
+
+/// Resumes a paused session: polls right away and emits the points, including superchats
+/// received while paused.
+#[tauri::command]
+async fn resume_monitoring(
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    set_paused(&state, false).await?;
+    println!("Monitoring resumed");
+    let _ = app.emit("monitoring-paused", false);
+    send_web_event(&state, WebEvent::Paused(false)).await;
+
+    // Demos have no video to poll; their next step brings the metrics up to date
+    for stream in all_streams(&state).await {
+        if let Err(e) = update_metrics(&state, &app, &stream).await {
+            eprintln!("Failed to update metrics: {}", e);
+            emit_monitoring_error(&app, "update_metrics", &e);
+        }
+    }
+    emit_points(&state, &app).await;
+    Ok(())
+}
+
+/// Pauses or resumes the session; only a running session can be paused
+async fn set_paused(state: &Arc<AppState>, paused: bool) -> Result<(), String> {
+    let monitoring = state.is_monitoring.read().await;
+    let mut is_paused = state.is_paused.write().await;
+    if paused && !*monitoring {
+        return Err("Not monitoring".into());
+    }
+    if *is_paused == paused {
+        return Err(if paused {
+            "Already paused"
+        } else {
+            "Not paused"
+        }
+        .into());
+    }
+    *is_paused = paused;
+    Ok(())
+}
+
+/// Plays a scripted stream (`quiet` or `hype`) through the normal points pipeline for demo
+/// footage, without a sidecar. Points start from zero; `stop_monitoring` ends it early.
+#[tauri::command]
+async fn start_demo(
+    scenario: String,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let scenario = demo::DemoScenario::find(&scenario)?;
+    let start_generation = {
+        let mut monitoring = state.is_monitoring.write().await;
+        if *monitoring {
+            return Err("Already monitoring".into());
+        }
+
+        {
+            let mut points = state.points.write().await;
+            *points = points::PointState::default();
+        }
+        {
+            let mut metrics = state.raw_metrics.write().await;
+            *metrics = points::RawMetrics {
+                concurrent_viewers: scenario.viewers_at(0),
+                initial_subscribers: scenario.initial_subscribers,
+                current_subscribers: scenario.initial_subscribers,
+                ..Default::default()
+            };
+        }
+        {
+            let mut bonus_given = state.concurrent_bonus_given.write().await;
+            *bonus_given = false;
+        }
+        {
+            let mut milestones_reached = state.milestones_reached.write().await;
+            milestones_reached.clear();
+        }
+        {
+            let mut cap_reached = state.cap_reached.write().await;
+            *cap_reached = false;
+        }
+        {
+            let mut counters = state.session_counters.write().await;
+            *counters = points::SessionCounters::default();
+        }
+        {
+            let mut stats = state.superchat_stats.write().await;
+            *stats = points::SuperchatStats::default();
+        }
+        {
+            let mut last_update = state.last_metrics_update.write().await;
+            *last_update = Some(Instant::now());
+        }
+        {
+            let mut started_at = state.monitoring_started_at.write().await;
+            *started_at = Some(Instant::now());
+        }
+        {
+            let mut markers = state.markers.write().await;
+            markers.clear();
+        }
+        {
+            let mut history = state.metric_history.write().await;
+            history.clear();
+        }
+        {
+            let mut history = state.history.write().await;
+            history.clear();
+        }
+
+        {
+            let mut paused = state.is_paused.write().await;
+            *paused = false;
+        }
+
+        // No video ID is stored, so the demo is never saved to the session history
+        *monitoring = true;
+        state.start_generation.load(Ordering::SeqCst)
+    };
+    println!("Demo started: {}", scenario.name);
+
+    emit_points(&state, &app).await;
+
+    let (superchat_tx, superchat_rx) = mpsc::unbounded_channel();
+    spawn_superchat_handler(state.inner().clone(), app.clone(), None, superchat_rx);
+
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(demo::DEMO_TICK_SECONDS));
+
+        for tick in 0..scenario.ticks {
+            ticker.tick().await;
+
+            if !*state.is_monitoring.read().await
+                || state.start_generation.load(Ordering::SeqCst) != start_generation
+            {
+                println!("Demo stopped");
+                return;
+            }
+            if *state.is_paused.read().await {
+                continue;
+            }
+
+            let viewers = scenario.viewers_at(tick);
+            {
+                let mut metrics = state.raw_metrics.write().await;
+                metrics.concurrent_viewers = viewers;
+                metrics.like_count += scenario.likes_per_tick;
+                if scenario.gains_subscriber_at(tick) {
+                    metrics.current_subscribers += 1;
+                }
+            }
+            // Subscriber points are normally entered by hand
+            if scenario.gains_subscriber_at(tick) {
+                let mut points = state.points.write().await;
+                points.subscribers += 1;
+            }
+            {
+                let mut counters = state.session_counters.write().await;
+                counters.record_viewers(viewers);
+            }
+            {
+                let mut last_update = state.last_metrics_update.write().await;
+                *last_update = Some(Instant::now());
+            }
+
+            for (index, superchat) in scenario.superchats_at(tick).enumerate() {
+                let _ = superchat_tx.send(sidecar::SuperchatEventData {
+                    id: format!("demo-{}-{}", tick, index),
+                    author: superchat.author.to_string(),
+                    amount: superchat.amount,
+                    currency: "JPY".to_string(),
+                    message: superchat.message.to_string(),
+                    timestamp: unix_now_ms(),
+                });
+            }
+
+            emit_points(&state, &app).await;
+            record_metric_sample(&state).await;
+        }
+
+        // Only end the demo if stop_monitoring or a new session has not already taken over
+        if state.start_generation.load(Ordering::SeqCst) == start_generation {
+            let _ = shutdown_monitoring(&state).await;
+            println!("Demo finished: {}", scenario.name);
+            let _ = app.emit("demo-finished", scenario.name);
+        }
+    });
+
+    Ok(())
+}
+
+async fn shutdown_monitoring(state: &Arc<AppState>) -> Result<(), String> {
+    end_session(state, false).await
+}
+
+async fn end_session(state: &Arc<AppState>, force: bool) -> Result<(), String> {
+    {
+        let mut monitoring = state.is_monitoring.write().await;
+        *monitoring = false;
+        // Cancel any start_monitoring that is still in progress
+        state.start_generation.fetch_add(1, Ordering::SeqCst);
+    }
+    {
+        let mut paused = state.is_paused.write().await;
+        *paused = false;
+    }
+
+    // A sidecar that fails to stop does not keep the session from ending
+    let stopped = stop_sidecars(&all_streams(state).await, force).await;
+
+    save_session_history(state).await;
+    // The session ended on purpose, so there is nothing to resume
+    if let Err(e) = state::SessionState::clear() {
+        eprintln!("Failed to clear saved session: {}", e);
+    }
+
+    // Clear monitoring info
+    {
+        let mut streams = state.streams.write().await;
+        streams.clear();
+    }
+    {
+        let mut vid = state.monitoring_video_id.write().await;
+        *vid = None;
+    }
+    {
+        let mut last_update = state.last_metrics_update.write().await;
+        *last_update = None;
+    }
+    {
+        let mut started_at = state.monitoring_started_at.write().await;
+        *started_at = None;
+    }
+
+    println!("Monitoring stopped");
+    stopped
+}
+
+/// Stops every stream's sidecar, even if one of them fails to stop, and returns the first error
+async fn stop_sidecars(streams: &[Arc<StreamSession>], force: bool) -> Result<(), String> {
+    let mut result = Ok(());
+
+    // A hung call may be holding the sidecar lock; killing through a shared lock ends that
+    // call right away so the lock below can be taken
+    if force {
+        for stream in streams {
+            let sidecar_guard = stream.sidecar.read().await;
+            if let Some(sidecar) = sidecar_guard.as_ref() {
+                match sidecar.kill().await {
+                    Ok(()) => println!("Sidecar force-stopped: {}", stream.video_id),
+                    Err(e) => result = result.and(Err(e)),
+                }
+            }
+        }
+    }
+
+    for stream in streams {
+        let mut sidecar_guard = stream.sidecar.write().await;
+        if let Some(mut sidecar) = sidecar_guard.take()
+            && let Err(e) = sidecar.stop().await
+        {
+            result = result.and(Err(e));
+        }
+    }
+    result
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn unix_now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Appends the session that is being stopped to the on-disk history.
+async fn save_session_history(state: &Arc<AppState>) {
+    let Some(video_id) = state.monitoring_video_id.read().await.clone() else {
+        return;
+    };
+    let analytics = session_analytics(state).await;
+    let ended_at = unix_now();
+    let started_at = ended_at.saturating_sub(analytics.duration_seconds);
+
+    let record = history::SessionRecord {
+        entry: history::SessionEntry {
+            id: format!("{}-{}", started_at, video_id),
+            title: primary_stream(state)
+                .await
+                .map(|stream| stream.title.clone())
+                .unwrap_or_default(),
+            video_id,
+            started_at,
+            ended_at,
+            final_points: analytics.points.total,
+            peak_viewers: analytics.peak_viewers,
+        },
+        metrics: state.raw_metrics.read().await.clone(),
+        analytics,
+        markers: state.markers.read().await.clone(),
+    };
+    if let Err(e) = history::record_session(&record) {
+        eprintln!("Failed to save session history: {}", e);
+    }
+}
+
+/// Longest marker label accepted, in characters
+const MARKER_LABEL_MAX_CHARS: usize = 100;
+
+/// Drops a timestamped marker on the current stream (e.g. "big superchat", "raid").
+/// Markers are saved with the session history.
+#[tauri::command]
+async fn add_marker(
+    label: String,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<history::Marker, String> {
+    let started_at = state
+        .monitoring_started_at
+        .read()
+        .await
+        .ok_or("Not monitoring")?;
+    let label: String = label.trim().chars().take(MARKER_LABEL_MAX_CHARS).collect();
+    if label.is_empty() {
+        return Err("Marker label is empty".into());
+    }
+
+    let marker = history::Marker {
+        elapsed_seconds: started_at.elapsed().as_secs(),
+        label,
+        created_at: unix_now(),
+    };
+    {
+        let mut markers = state.markers.write().await;
+        markers.push(marker.clone());
+    }
+    let _ = app.emit("marker-added", &marker);
+    Ok(marker)
+}
+
+/// Returns the markers of the current (or last) session, oldest first.
+#[tauri::command]
+async fn get_markers(state: State<'_, Arc<AppState>>) -> Result<Vec<history::Marker>, String> {
+    Ok(state.markers.read().await.clone())
+}
+
+/// Writes the current points and monitoring info to the session file; returns its path.
+#[tauri::command]
+async fn save_session_now(state: State<'_, Arc<AppState>>) -> Result<String, String> {
+    let path = persist_session(&state).await?;
+    println!("Session saved to {}", path.display());
+    Ok(path.display().to_string())
+}
+
+async fn persist_session(state: &Arc<AppState>) -> Result<std::path::PathBuf, String> {
+    state::SessionState {
+        video_id: state.monitoring_video_id.read().await.clone(),
+        channel_id: match primary_stream(state).await {
+            Some(stream) => Some(stream.channel_id.read().await.clone()),
+            None => None,
+        },
+        points: state.points.read().await.clone(),
+        metrics: match primary_stream(state).await {
+            Some(stream) => Some(stream.metrics.read().await.clone()),
+            None => None,
+        },
+        concurrent_bonus_given: *state.concurrent_bonus_given.read().await,
+        saved_at: unix_now(),
+        markers: state.markers.read().await.clone(),
+    }
+    .save()
+}
+
+/// Restarts monitoring the video of the session saved before the app last closed or crashed.
+/// Points restored at startup carry over, and the saved subscriber baseline and superchat
+/// total are kept instead of starting from the current counts.
+#[tauri::command]
+async fn resume_last_session(
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let session = state::SessionState::load().ok_or("No saved session to resume")?;
+    let video_id = session
+        .video_id
+        .clone()
+        .ok_or("No saved session to resume")?;
+    start_monitoring_from(video_id, None, Some(session), state, app).await
+}
+
+/// Persisted files (config, saved session, session history) with their sizes
+#[tauri::command]
+fn list_stored_data() -> Vec<storage::StoredItem> {
+    storage::list()
+}
+
+/// Deletes the selected kinds of persisted data and reports the outcome for each.
+/// The settings in use stay in effect until restart even when `config` is removed.
+#[tauri::command]
+fn clear_stored_data(kinds: Vec<String>) -> Vec<storage::ClearResult> {
+    kinds.iter().map(|kind| storage::clear(kind)).collect()
+}
+
+#[tauri::command]
+async fn list_sessions() -> Result<Vec<history::SessionEntry>, String> {
+    history::list_sessions()
+}
+
+#[tauri::command]
+async fn load_session_summary(id: String) -> Result<history::SessionRecord, String> {
+    history::load_session(&id)
+}
+
+#[tauri::command]
+async fn add_manual_points(
+    amount: i64,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let (points, metrics, capped, new_milestones) = add_manual(&state, amount).await;
+    println!("Added {} manual points. Total: {}", amount, points.total);
+    publish_points(&state, &app, points, metrics, capped, new_milestones).await;
+
+    Ok(())
+}
+
+/// Adds to the manual count and recalculates the total from every source, so the total cap
+/// also applies here
+async fn add_manual(
+    state: &Arc<AppState>,
+    amount: i64,
+) -> (
+    points::PointState,
+    points::RawMetrics,
+    bool,
+    Vec<config::Milestone>,
+) {
+    state.points.write().await.manual += amount;
+    recalculate_points(state).await
+}
+
+#[tauri::command]
+async fn add_visitor_points(
+    amount: i64,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    {
+        let mut points = state.points.write().await;
+        points.visitor += amount;
+    }
+
+    // Recalculate the total from every source so the total cap also applies here
+    emit_points(&state, &app).await;
+    let points = state.points.read().await.clone();
+
+    println!("Added {} visitor points. Total: {}", amount, points.total);
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn add_subscriber_points(
     amount: i64,
     state: State<'_, Arc<AppState>>,
     app: tauri::AppHandle,
-) -> Result<(), String> {
-    let (points, metrics) = {
-        let mut points = state.points.write().await;
-        points.subscribers += amount;
-        points.total += (amount as f64 / config::POINTS_CONFIG.subscriber_rate) as i64;
-        let metrics = state.raw_metrics.read().await;
-        (points.clone(), metrics.clone())
+) -> Result<(), String> {
+    {
+        let mut points = state.points.write().await;
+        points.subscribers += amount;
+    }
+
+    // Recalculate the total from every source so the total cap also applies here
+    emit_points(&state, &app).await;
+    let points = state.points.read().await.clone();
+
+    println!(
+        "Added {} subscriber points. Total: {}",
+        amount, points.total
+    );
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_points(state: State<'_, Arc<AppState>>) -> Result<points::PointState, String> {
+    let points = state.points.read().await;
+    Ok(points.clone())
+}
+
+/// Returns how far each rate-based source is toward its next whole point.
+#[tauri::command]
+async fn get_source_progress(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<points::SourceProgress>, String> {
+    let points_config = state.config.read().await.points.clone();
+    let metrics = state.raw_metrics.read().await;
+    let points = state.points.read().await;
+    Ok(points::SourceProgress::all(
+        &metrics,
+        &points,
+        &points_config,
+    ))
+}
+
+/// Clears points and session metrics and pushes the update to every overlay right away.
+/// Works the same whether or not monitoring is running; later polls build on the cleared state.
+#[tauri::command]
+async fn reset_points(
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    clear_points(&state).await;
+    if let Err(e) = state::SessionState::clear() {
+        eprintln!("Failed to clear saved session: {}", e);
+    }
+
+    let points = state.points.read().await.clone();
+    let metrics = state.raw_metrics.read().await.clone();
+    println!("Points reset");
+
+    send_points_update(&state, &app, points, metrics).await;
+
+    Ok(())
+}
+
+async fn clear_points(state: &Arc<AppState>) {
+    // Reset points
+    {
+        let mut points = state.points.write().await;
+        *points = points::PointState::default();
+    }
+
+    // Reset raw metrics (keep initial_subscribers)
+    {
+        let mut metrics = state.raw_metrics.write().await;
+        reset_metrics(&mut metrics);
+    }
+    let streams = all_streams(state).await;
+    for stream in &streams {
+        reset_metrics(&mut *stream.metrics.write().await);
+        // A held-back viewer reading refers to the cleared metrics
+        *stream.viewer_dip_pending.write().await = false;
+    }
+    if !streams.is_empty() {
+        sync_combined_metrics(state).await;
+    }
+
+    // Reset concurrent bonus flag
+    {
+        let mut bonus_given = state.concurrent_bonus_given.write().await;
+        *bonus_given = false;
+    }
+    {
+        let mut milestones_reached = state.milestones_reached.write().await;
+        milestones_reached.clear();
+    }
+    {
+        let mut cap_reached = state.cap_reached.write().await;
+        *cap_reached = false;
+    }
+
+    // Reset donor tracking
+    {
+        let mut stats = state.superchat_stats.write().await;
+        *stats = points::SuperchatStats::default();
+    }
+    {
+        let mut counters = state.session_counters.write().await;
+        *counters = points::SessionCounters::default();
+    }
+}
+
+fn reset_metrics(metrics: &mut points::RawMetrics) {
+    let initial_subs = metrics.initial_subscribers;
+    *metrics = points::RawMetrics {
+        initial_subscribers: initial_subs,
+        current_subscribers: initial_subs,
+        ..Default::default()
+    };
+}
+
+/// Appends the current metrics and total to the in-memory time series
+async fn record_metric_sample(state: &Arc<AppState>) {
+    let sample = points::MetricSample::new(
+        monitoring_seconds(state).await,
+        &*state.raw_metrics.read().await,
+        &*state.points.read().await,
+    );
+    let mut history = state.metric_history.write().await;
+    history.push_back(sample);
+    if history.len() > config::METRIC_HISTORY_LIMIT {
+        history.pop_front();
+    }
+}
+
+async fn record_snapshot(
+    state: &Arc<AppState>,
+    points: &points::PointState,
+    metrics: &points::RawMetrics,
+) {
+    let limit = state.config.read().await.session.history_limit;
+    let snapshot = points::MetricSnapshot {
+        timestamp: unix_now_ms(),
+        points: points.clone(),
+        metrics: metrics.clone(),
+    };
+    points::push_snapshot(&mut *state.history.write().await, snapshot, limit);
+}
+
+/// Returns a snapshot of the points and metrics for every points update of the session
+/// (up to `session.history_limit`, oldest first). Kept after monitoring stops.
+#[tauri::command]
+async fn get_history(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<points::MetricSnapshot>, String> {
+    Ok(state.history.read().await.iter().cloned().collect())
+}
+
+/// Writes the points update history to a CSV file at `path`, with the session's markers
+/// alongside the update they follow. Returns the number of data rows written.
+#[tauri::command]
+async fn export_history_csv(
+    path: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<usize, String> {
+    let snapshots: Vec<_> = state.history.read().await.iter().cloned().collect();
+    let markers = state.markers.read().await.clone();
+    let csv = export::history_csv(&snapshots, &markers);
+    std::fs::write(&path, csv).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(snapshots.len())
+}
+
+/// Drops the recorded points update history.
+#[tauri::command]
+async fn clear_history(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.history.write().await.clear();
+    Ok(())
+}
+
+/// Returns the session's per-poll metrics and totals merged into at most `buckets` time buckets
+/// (viewers and likes averaged, other values as of the end of each bucket).
+#[tauri::command]
+async fn get_history_downsampled(
+    buckets: usize,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<points::MetricSample>, String> {
+    if buckets == 0 {
+        return Err("Bucket count must be greater than 0".into());
+    }
+    let history = state.metric_history.read().await;
+    let samples: Vec<_> = history.iter().cloned().collect();
+    Ok(points::MetricSample::downsample(&samples, buckets))
+}
+
+/// Seconds since monitoring started, or 0 when not monitoring
+async fn monitoring_seconds(state: &Arc<AppState>) -> u64 {
+    state
+        .monitoring_started_at
+        .read()
+        .await
+        .map(|started_at| started_at.elapsed().as_secs())
+        .unwrap_or(0)
+}
+
+async fn session_analytics(state: &Arc<AppState>) -> points::SessionAnalytics {
+    let duration_seconds = monitoring_seconds(state).await;
+    // Same lock order as emit_points (metrics, then points)
+    let metrics = state.raw_metrics.read().await;
+    let points = state.points.read().await;
+    let counters = state.session_counters.read().await;
+    points::SessionAnalytics::compute(&points, &metrics, &counters, duration_seconds)
+}
+
+/// The summary of the most recent stop-and-reset, if one was saved
+#[tauri::command]
+fn get_last_session_summary() -> Option<state::SessionSummary> {
+    state::SessionSummary::load()
+}
+
+/// Estimates when the points goal will be reached at the session's average pace so far.
+#[tauri::command]
+async fn estimate_time_to_goal(
+    state: State<'_, Arc<AppState>>,
+) -> Result<points::GoalEstimate, String> {
+    let (points_config, goal_counts_manual, progress_metric, goals) = {
+        let config = state.config.read().await;
+        (
+            config.points.clone(),
+            config.overlay.goal_counts_manual,
+            config.overlay.progress_metric,
+            config.overlay.goal_list(),
+        )
+    };
+    if progress_metric != config::ProgressMetric::Total {
+        return Err("Time to goal is only estimated for a points goal".into());
+    }
+    let points = state.points.read().await.clone();
+    let final_goal = goals.last().copied().unwrap_or_default();
+    let goal_points = goal_points(&points, &points_config, goal_counts_manual, final_goal);
+    // Estimates the active goal, like the overlay shows
+    let goal = points::GoalStatus::compute(&goals, goal_points).active;
+    Ok(points::GoalEstimate::compute(
+        goal_points,
+        goal,
+        monitoring_seconds(&state).await,
+    ))
+}
+
+#[tauri::command]
+async fn get_session_analytics(
+    state: State<'_, Arc<AppState>>,
+) -> Result<points::SessionAnalytics, String> {
+    Ok(session_analytics(&state).await)
+}
+
+#[tauri::command]
+async fn stop_and_reset(
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    // Capture the final state before anything is cleared
+    let summary = state::SessionSummary {
+        video_id: state.monitoring_video_id.read().await.clone(),
+        metrics: state.raw_metrics.read().await.clone(),
+        analytics: session_analytics(&state).await,
+    };
+
+    shutdown_monitoring(&state).await?;
+    let _ = app.emit("session-summary", &summary);
+    // Keep the recap available after the window is reopened or the app restarts
+    if let Err(e) = summary.save() {
+        eprintln!("Failed to save session summary: {}", e);
+    }
+
+    clear_points(&state).await;
+
+    let points = state.points.read().await.clone();
+    let metrics = state.raw_metrics.read().await.clone();
+    println!("Monitoring stopped and points reset");
+
+    send_points_update(&state, &app, points, metrics).await;
+
+    Ok(())
+}
+
+/// Stops the prewarmed sidecar and every stream's sidecar, for app exit
+async fn stop_all_sidecars(state: &Arc<AppState>) {
+    let mut sidecars: Vec<_> = state
+        .prewarmed_sidecar
+        .write()
+        .await
+        .take()
+        .into_iter()
+        .collect();
+    for stream in all_streams(state).await {
+        sidecars.extend(stream.sidecar.write().await.take());
+    }
+    for mut sidecar in sidecars {
+        let _ = sidecar.stop().await;
+        println!("Sidecar stopped on exit");
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct HeartbeatPayload {
+    is_monitoring: bool,
+    timestamp: u64,
+}
+
+async fn run_heartbeat(state: Arc<AppState>, app: tauri::AppHandle) {
+    let mut ticker = interval(Duration::from_secs(config::HEARTBEAT_INTERVAL_SECONDS));
+
+    loop {
+        ticker.tick().await;
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let payload = HeartbeatPayload {
+            is_monitoring: *state.is_monitoring.read().await,
+            timestamp,
+        };
+        let _ = app.emit("heartbeat", &payload);
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ActiveModifier {
+    /// One-time bonus once concurrent viewers exceed the goal
+    ConcurrentBonus {
+        goal: i64,
+        bonus: i64,
+        reached: bool,
+    },
+    /// Named points profile whose rates are in effect
+    Profile { name: String },
+}
+
+/// Lists the scoring modifiers currently in effect.
+#[tauri::command]
+async fn get_active_modifiers(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<ActiveModifier>, String> {
+    let bonus_given = *state.concurrent_bonus_given.read().await;
+    Ok(active_modifiers(&*state.config.read().await, bonus_given))
+}
+
+fn active_modifiers(config: &config::Config, bonus_given: bool) -> Vec<ActiveModifier> {
+    let mut modifiers = Vec::new();
+    // A disabled source awards no bonus, so it is not listed
+    if config.points.concurrent_enabled {
+        modifiers.push(ActiveModifier::ConcurrentBonus {
+            goal: config::CONCURRENT_BONUS_THRESHOLD,
+            bonus: config::CONCURRENT_BONUS_POINTS,
+            reached: bonus_given,
+        });
+    }
+    if let Some(name) = config.active_profile.clone() {
+        modifiers.push(ActiveModifier::Profile { name });
+    }
+    modifiers
+}
+
+#[tauri::command]
+async fn get_superchat_stats(
+    state: State<'_, Arc<AppState>>,
+) -> Result<points::SuperchatStats, String> {
+    let stats = state.superchat_stats.read().await;
+    Ok(stats.clone())
+}
+
+/// Clears donor tracking only; `superchat_amount` and the derived points are kept.
+#[tauri::command]
+async fn clear_superchat_data(
+    state: State<'_, Arc<AppState>>,
+) -> Result<points::SuperchatStats, String> {
+    let mut stats = state.superchat_stats.write().await;
+    *stats = points::SuperchatStats::default();
+    println!("Superchat data cleared");
+    Ok(stats.clone())
+}
+
+#[tauri::command]
+async fn open_viewer_window(app: tauri::AppHandle) -> Result<(), String> {
+    let mut builder = WebviewWindowBuilder::new(
+        &app,
+        "viewer",
+        tauri::WebviewUrl::App("/viewer.html".into()),
+    )
+    .title("YT Point - 視聴者用表示")
+    .transparent(true)
+    .decorations(true)
+    .always_on_top(true);
+
+    // Restore the last layout the streamer left the window in
+    builder = match state::WindowLayout::load() {
+        Some(layout) => builder
+            .inner_size(layout.width, layout.height)
+            .position(layout.x, layout.y),
+        None => builder.inner_size(config::VIEWER_WINDOW_WIDTH, config::VIEWER_WINDOW_HEIGHT),
+    };
+    let viewer = builder.build().map_err(|e| e.to_string())?;
+
+    let window = viewer.clone();
+    viewer.on_window_event(move |event| {
+        if matches!(
+            event,
+            tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_)
+        ) && let Err(e) = save_viewer_layout(&window)
+        {
+            eprintln!("Failed to save viewer window layout: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+fn save_viewer_layout(window: &tauri::WebviewWindow) -> Result<(), String> {
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let position = window
+        .outer_position()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale);
+    let size = window
+        .inner_size()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale);
+    state::WindowLayout {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    }
+    .save()
+}
+
+/// Forgets the saved viewer window layout and moves an open viewer back to the default.
+#[tauri::command]
+async fn reset_viewer_window_layout(app: tauri::AppHandle) -> Result<(), String> {
+    state::WindowLayout::clear()?;
+    if let Some(viewer) = app.get_webview_window("viewer") {
+        viewer
+            .set_size(tauri::LogicalSize::new(
+                config::VIEWER_WINDOW_WIDTH,
+                config::VIEWER_WINDOW_HEIGHT,
+            ))
+            .map_err(|e| e.to_string())?;
+        viewer.center().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn open_youtube_login(app: tauri::AppHandle) -> Result<(), String> {
+    // Close existing window if any
+    if let Some(window) = app.get_webview_window("youtube-login") {
+        let _ = window.close();
+        // Wait a bit for the window to close
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // Create new window
+    let login_window = WebviewWindowBuilder::new(
+        &app,
+        "youtube-login",
+        tauri::WebviewUrl::External("https://studio.youtube.com".parse().unwrap()),
+    )
+    .title("YouTube Login")
+    .inner_size(1000.0, 700.0)
+    .build()
+    .map_err(|e| e.to_string())?;
+
+    // Listen for window close and check login status
+    let app_handle = app.clone();
+    login_window.on_window_event(move |event| {
+        if let tauri::WindowEvent::CloseRequested { .. } = event {
+            let app = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                // Use main window to check cookies (cookie store is shared across windows)
+                if let Some(window) = app.get_webview_window("main") {
+                    let url: url::Url = "https://www.youtube.com".parse().unwrap();
+                    if let Ok(cookies) = window.cookies_for_url(url) {
+                        let has_sapisid = cookies.iter().any(|c| c.name() == "SAPISID");
+                        let has_secure_3psid = cookies.iter().any(|c| c.name() == "__Secure-3PSID");
+                        let is_logged_in = has_sapisid && has_secure_3psid;
+                        let _ = app.emit("youtube-login-status", is_logged_in);
+                    }
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_youtube_cookies(app: tauri::AppHandle) -> Result<String, String> {
+    // Use youtube-login window if it exists, otherwise use main window
+    // (avoids creating a hidden YouTube page that crashes WebKit on WSL2)
+    let window = app
+        .get_webview_window("youtube-login")
+        .or_else(|| app.get_webview_window("main"))
+        .ok_or("No window available")?;
+
+    // Use Tauri's cookies_for_url API to get all cookies including HTTP-only ones
+    // Cookie store is shared across all windows, so any window can be used
+    let url: url::Url = "https://www.youtube.com".parse().unwrap();
+    let cookies: Vec<Cookie<'_>> = window
+        .cookies_for_url(url)
+        .map_err(|e| format!("Failed to get cookies: {}", e))?;
+
+    // Format cookies as a string
+    let cookie_str: String = cookies
+        .iter()
+        .map(|c| format!("{}={}", c.name(), c.value()))
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    println!("Retrieved {} cookies", cookies.len());
+
+    // Check for auth cookies
+    let has_sapisid = cookies.iter().any(|c| c.name() == "SAPISID");
+    let has_secure_3psid = cookies.iter().any(|c| c.name() == "__Secure-3PSID");
+
+    if has_sapisid && has_secure_3psid {
+        println!("Auth cookies found: SAPISID and __Secure-3PSID");
+    } else {
+        println!("Warning: Some auth cookies may be missing");
+        println!("  SAPISID: {}", has_sapisid);
+        println!("  __Secure-3PSID: {}", has_secure_3psid);
+    }
+
+    Ok(cookie_str)
+}
+
+/// Signs out of YouTube: drops the login window's cookies and any imported ones, and
+/// re-initializes the running sidecars without authentication.
+#[tauri::command]
+async fn logout(state: State<'_, Arc<AppState>>, app: tauri::AppHandle) -> Result<(), String> {
+    // The cookie store is shared, so the main window can clear it when no login window is open
+    let cookie_window = app
+        .get_webview_window("youtube-login")
+        .or_else(|| app.get_webview_window("main"));
+    if let Some(window) = cookie_window {
+        for url in ["https://www.youtube.com", "https://accounts.google.com"] {
+            let url: url::Url = url.parse().unwrap();
+            let cookies = window
+                .cookies_for_url(url)
+                .map_err(|e| format!("Failed to get cookies: {}", e))?;
+            for cookie in cookies {
+                window
+                    .delete_cookie(cookie)
+                    .map_err(|e| format!("Failed to delete cookie: {}", e))?;
+            }
+        }
+    }
+    // destroy skips the close handler, which would report the login status mid-logout
+    if let Some(window) = app.get_webview_window("youtube-login") {
+        let _ = window.destroy();
+    }
+
+    {
+        let mut imported = state.imported_cookies.write().await;
+        *imported = None;
+    }
+    for stream in all_streams(&state).await {
+        let sidecar_guard = stream.sidecar.read().await;
+        if let Some(sidecar) = sidecar_guard.as_ref() {
+            sidecar.clear_cookies().await?;
+            sidecar.init().await?;
+        }
+    }
+    {
+        let sidecar_guard = state.prewarmed_sidecar.read().await;
+        if let Some(sidecar) = sidecar_guard.as_ref() {
+            sidecar.clear_cookies().await?;
+            sidecar.init().await?;
+        }
+    }
+
+    set_authenticated(&state, &app, false).await;
+    let _ = app.emit("youtube-login-status", false);
+    println!("Logged out of YouTube");
+    Ok(())
+}
+
+#[derive(Clone, serde::Serialize)]
+struct CookieImportResult {
+    cookie_count: usize,
+    /// Whether the cookies were applied to a running sidecar (otherwise used on next start)
+    applied: bool,
+    is_authenticated: bool,
+}
+
+/// Imports YouTube cookies from a Netscape-format cookie file exported from a browser.
+#[tauri::command]
+async fn import_cookies_from_file(
+    path: String,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<CookieImportResult, String> {
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read cookie file: {}", e))?;
+    let cookies = cookies::parse_netscape_cookies(&content);
+
+    let missing = cookies::missing_auth_cookies(&cookies);
+    if !missing.is_empty() {
+        return Err(format!("Missing auth cookies: {}", missing.join(", ")));
+    }
+
+    let cookie_str = cookies::to_cookie_header(&cookies);
+    println!("Imported {} cookies from {}", cookies.len(), path);
+
+    // Apply to the running sidecars right away, if any
+    let applied = match reinit_sidecars(&state, &app, Some(&cookie_str)).await? {
+        Some(is_authenticated) => {
+            set_authenticated(&state, &app, is_authenticated).await;
+            true
+        }
+        None => false,
     };
 
-    println!(
-        "Added {} subscriber points. Total: {}",
-        amount, points.total
-    );
+    {
+        let mut imported = state.imported_cookies.write().await;
+        *imported = Some(cookie_str);
+    }
 
-    // Emit event with full payload (points + metrics)
-    let payload = PointsUpdatePayload {
-        points: points.clone(),
-        metrics: metrics.clone(),
-        config: config::POINTS_CONFIG.clone(),
+    Ok(CookieImportResult {
+        cookie_count: cookies.len(),
+        applied,
+        is_authenticated: *state.is_authenticated.read().await,
+    })
+}
+
+#[derive(serde::Serialize)]
+struct StateDump {
+    is_monitoring: bool,
+    is_authenticated: bool,
+    monitoring_video_id: Option<String>,
+    monitoring_channel_id: Option<String>,
+    streams: Vec<stream::StreamInfo>,
+    points: points::PointState,
+    metrics: points::RawMetrics,
+    config: config::Config,
+    server_url: Option<String>,
+}
+
+/// Snapshot of non-sensitive state for bug reports (cookies and tokens are excluded).
+///
+/// Available in debug builds, or in release builds when `YT_POINT_DEBUG` is set.
+#[tauri::command]
+async fn dump_state(state: State<'_, Arc<AppState>>) -> Result<StateDump, String> {
+    if !cfg!(debug_assertions) && std::env::var_os("YT_POINT_DEBUG").is_none() {
+        return Err("dump_state is disabled (set YT_POINT_DEBUG to enable)".into());
+    }
+
+    // Never expose the overlay server token
+    let mut config = state.config.read().await.clone();
+    if config.server.auth_token.is_some() {
+        config.server.auth_token = Some("<redacted>".into());
+    }
+
+    Ok(StateDump {
+        is_monitoring: *state.is_monitoring.read().await,
+        is_authenticated: *state.is_authenticated.read().await,
+        monitoring_video_id: state.monitoring_video_id.read().await.clone(),
+        monitoring_channel_id: match primary_stream(&state).await {
+            Some(stream) => Some(stream.channel_id.read().await.clone()),
+            None => None,
+        },
+        streams: list_streams(&state).await,
+        points: state.points.read().await.clone(),
+        metrics: state.raw_metrics.read().await.clone(),
+        config,
+        server_url: state.server_url.read().await.clone(),
+    })
+}
+
+#[derive(Clone, serde::Serialize)]
+struct PipelineStage {
+    stage: &'static str,
+    passed: bool,
+    detail: String,
+}
+
+/// Pushes synthetic metrics through calculation, recalculation and the web broadcast,
+/// checking each stage. Runs on a scratch state with its own broadcast channel, so the
+/// session, the main window and connected overlays never see the synthetic points.
+///
+/// Available in debug builds, or in release builds when `YT_POINT_DEBUG` is set.
+#[tauri::command]
+async fn self_test_pipeline(state: State<'_, Arc<AppState>>) -> Result<Vec<PipelineStage>, String> {
+    if !cfg!(debug_assertions) && std::env::var_os("YT_POINT_DEBUG").is_none() {
+        return Err("self_test_pipeline is disabled (set YT_POINT_DEBUG to enable)".into());
+    }
+
+    let app_config = state.config.read().await.clone();
+    Ok(run_self_test(app_config).await)
+}
+
+/// Runs the self-test stages against `app_config`
+async fn run_self_test(app_config: config::Config) -> Vec<PipelineStage> {
+    let points_config = app_config.points.clone();
+    // Stays under the concurrent bonus threshold so the expected total is just the rates
+    let synthetic = points::RawMetrics {
+        superchat_amount: 10_000,
+        concurrent_viewers: config::CONCURRENT_BONUS_THRESHOLD,
+        like_count: 200,
+        initial_subscribers: 1_000,
+        current_subscribers: 1_000,
+        ..Default::default()
     };
-    let _ = app.emit("points-update", &payload);
+    // Disabled sources contribute nothing
+    let expected_superchat = if points_config.superchat_enabled {
+        points::clamp_to_cap(
+            (10_000.0 / points_config.superchat_rate) as i64,
+            points_config.superchat_cap,
+        )
+    } else {
+        0
+    };
+    let expected_likes = if points_config.like_enabled {
+        points::clamp_to_cap(
+            (200.0 / points_config.like_rate) as i64,
+            points_config.like_cap,
+        )
+    } else {
+        0
+    };
+    // Milestones the synthetic total passes are marked as reached so no event is announced
+    let synthetic_milestones: Vec<config::Milestone> = points_config
+        .milestones_reached_at(synthetic.superchat_amount)
+        .cloned()
+        .collect();
+    let expected_bonus: i64 = synthetic_milestones
+        .iter()
+        .map(|milestone| milestone.bonus_points)
+        .sum();
+    let expected_total = (expected_superchat + expected_likes + expected_bonus)
+        .min(points_config.total_cap.unwrap_or(i64::MAX));
 
-    // Broadcast to web clients
-    let _ = state.web_broadcast.send(PointsPayload {
-        points,
-        metrics,
-        config: config::POINTS_CONFIG.clone(),
+    let mut stages = Vec::new();
+
+    let calculated =
+        points::PointState::calculate(&synthetic, &points::PointInputs::default(), &points_config);
+    stages.push(PipelineStage {
+        stage: "calculate",
+        passed: calculated.superchat == expected_superchat && calculated.likes == expected_likes,
+        detail: format!(
+            "superchat {} (expected {}), likes {} (expected {})",
+            calculated.superchat, expected_superchat, calculated.likes, expected_likes
+        ),
+    });
+
+    let scratch = Arc::new(AppState::new(app_config, state::SessionState::default()));
+    *scratch.raw_metrics.write().await = synthetic;
+    *scratch.milestones_reached.write().await = synthetic_milestones;
+
+    let mut rx = scratch.web_broadcast.subscribe();
+    let (recalculated, metrics, _, _) = recalculate_points(&scratch).await;
+    stages.push(PipelineStage {
+        stage: "recalculate",
+        passed: recalculated.total == expected_total,
+        detail: format!("total {} (expected {})", recalculated.total, expected_total),
+    });
+
+    let (payload, _) = points_payload(&scratch, recalculated, metrics).await;
+    broadcast_points(&scratch, payload).await;
+    let wait = Duration::from_secs(1);
+    let delivered = tokio::time::timeout(wait, rx.recv()).await;
+    stages.push(match delivered {
+        Ok(Ok(WebEvent::Points(payload))) => PipelineStage {
+            stage: "broadcast",
+            passed: payload.points.total == expected_total,
+            detail: format!(
+                "received total {} (expected {})",
+                payload.points.total, expected_total
+            ),
+        },
+        Ok(Ok(_)) => PipelineStage {
+            stage: "broadcast",
+            passed: false,
+            detail: "received a non-points event".to_string(),
+        },
+        Ok(Err(e)) => PipelineStage {
+            stage: "broadcast",
+            passed: false,
+            detail: format!("broadcast channel error: {}", e),
+        },
+        Err(_) => PipelineStage {
+            stage: "broadcast",
+            passed: false,
+            detail: format!("nothing received within {} ms", wait.as_millis()),
+        },
     });
 
+    stages
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ReachabilityReport {
+    url: String,
+    status: u16,
+    latency_ms: u64,
+}
+
+/// Makes a real HTTP request to the overlay server to confirm it is actually serving.
+#[tauri::command]
+async fn verify_viewer_reachable(
+    state: State<'_, Arc<AppState>>,
+) -> Result<ReachabilityReport, String> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let server_url = state
+        .server_url
+        .read()
+        .await
+        .clone()
+        .ok_or("Web server is not running")?;
+    let url: url::Url = server_url
+        .parse()
+        .map_err(|e| format!("Invalid server URL: {}", e))?;
+    let host = url.host_str().ok_or("Server URL has no host")?.to_string();
+    let port = url
+        .port_or_known_default()
+        .ok_or("Server URL has no port")?;
+
+    let started = Instant::now();
+    let request = async {
+        let mut stream = tokio::net::TcpStream::connect((host.as_str(), port))
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {}", server_url, e))?;
+        let request = format!(
+            "GET / HTTP/1.1\r\nHost: {}:{}\r\nConnection: close\r\n\r\n",
+            host, port
+        );
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+
+        let mut response = Vec::new();
+        stream
+            .read_to_end(&mut response)
+            .await
+            .map_err(|e| format!("Failed to read response: {}", e))?;
+        Ok::<_, String>(response)
+    };
+    let response = tokio::time::timeout(Duration::from_secs(5), request)
+        .await
+        .map_err(|_| format!("Timed out waiting for {}", server_url))??;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    // Status line: "HTTP/1.1 200 OK"
+    let status = String::from_utf8_lossy(&response)
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or("Invalid HTTP response from server")?;
+    if !(200..300).contains(&status) {
+        return Err(format!("Server responded with status {}", status));
+    }
+
+    Ok(ReachabilityReport {
+        url: server_url,
+        status,
+        latency_ms,
+    })
+}
+
+#[tauri::command]
+async fn get_server_url(state: State<'_, Arc<AppState>>) -> Result<Option<String>, String> {
+    let url = state.server_url.read().await;
+    Ok(url.clone())
+}
+
+#[derive(Clone, serde::Serialize)]
+struct OverlayLayoutInfo {
+    #[serde(flatten)]
+    layout: web_server::OverlayLayout,
+    /// Full URL to open or add as a browser source
+    preview_url: String,
+}
+
+/// Lists the overlay layouts the server provides, with their preview URLs.
+#[tauri::command]
+async fn list_overlay_layouts(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<OverlayLayoutInfo>, String> {
+    let server_url = state
+        .server_url
+        .read()
+        .await
+        .clone()
+        .ok_or("Web server is not running")?;
+    Ok(web_server::OVERLAY_LAYOUTS
+        .iter()
+        .map(|layout| OverlayLayoutInfo {
+            layout: *layout,
+            preview_url: format!("{}{}", server_url.trim_end_matches('/'), layout.path),
+        })
+        .collect())
+}
+
+/// Writes the overlay page to `path` as a standalone HTML file with the current overlay settings.
+/// It streams from `server_url`, or from the running overlay server when omitted.
+#[tauri::command]
+async fn export_viewer_html(
+    path: String,
+    server_url: Option<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let server_url = match server_url {
+        Some(url) => url,
+        None => state
+            .server_url
+            .read()
+            .await
+            .clone()
+            .ok_or("Web server is not running")?,
+    };
+    let url: url::Url = server_url
+        .parse()
+        .map_err(|e| format!("Invalid server URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Server URL must be http or https: {}", server_url));
+    }
+
+    let overlay = state.config.read().await.overlay.clone();
+    let html = web_server::render_standalone_viewer(&overlay, url.as_str());
+    std::fs::write(&path, html).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[derive(Clone, serde::Serialize)]
+struct NetworkExposure {
+    bind_host: String,
+    server_url: Option<String>,
+    loopback_only: bool,
+    auth_token_set: bool,
+    cors_policy: String,
+    /// "low" or "high"
+    risk: &'static str,
+    summary: String,
+}
+
+/// Describes who can reach the overlay server, so the UI can warn about exposure.
+#[tauri::command]
+async fn get_network_exposure(state: State<'_, Arc<AppState>>) -> Result<NetworkExposure, String> {
+    let bind_host = web_server::BIND_HOST;
+    let loopback_only = bind_host
+        .parse::<std::net::IpAddr>()
+        .is_ok_and(|ip| ip.is_loopback());
+    let auth_token_set = state.config.read().await.server.auth_token.is_some();
+
+    let (risk, summary) = match (loopback_only, auth_token_set) {
+        (true, true) => (
+            "low",
+            "Only this computer can reach the overlay server, and control endpoints require the auth token.",
+        ),
+        (true, false) => (
+            "low",
+            "Only this computer can reach the overlay server. Control endpoints are open to local programs and web pages; set server.auth_token to protect them.",
+        ),
+        (false, true) => (
+            "high",
+            "The overlay server is reachable from other devices on the network. Points and metrics are public to them; control endpoints require the auth token.",
+        ),
+        (false, false) => (
+            "high",
+            "The overlay server is reachable from other devices on the network without an auth token. Anyone on the network can read metrics and switch profiles.",
+        ),
+    };
+
+    Ok(NetworkExposure {
+        bind_host: bind_host.to_string(),
+        server_url: state.server_url.read().await.clone(),
+        loopback_only,
+        auth_token_set,
+        cors_policy: web_server::CORS_POLICY.to_string(),
+        risk,
+        summary: summary.to_string(),
+    })
+}
+
+/// Returns the effective configuration currently used for scoring.
+#[tauri::command]
+async fn get_config(state: State<'_, Arc<AppState>>) -> Result<config::Config, String> {
+    let config = state.config.read().await;
+    Ok(config.clone())
+}
+
+/// Replaces the configuration, saves it to `config.toml`, and recalculates the points
+/// from the current metrics so the new rates show up right away.
+#[tauri::command]
+async fn set_config(
+    new_config: config::Config,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    new_config.validate()?;
+    // Save first so the running config never differs from what is on disk
+    new_config.save()?;
+    let live_config = web_server::OverlayLiveConfig::new(&new_config.overlay);
+    replace_config(&state, new_config).await;
+    send_web_event(&state, WebEvent::OverlayConfig(live_config)).await;
+    emit_points(&state, &app).await;
+    Ok(())
+}
+
+/// Puts `new_config` into effect; points pick up the new rates on the next recalculation
+async fn replace_config(state: &Arc<AppState>, new_config: config::Config) {
+    let cap_changed = {
+        let mut config = state.config.write().await;
+        let cap_changed = config.points.total_cap != new_config.points.total_cap;
+        *config = new_config;
+        cap_changed
+    };
+    // A new cap gets its own `cap-reached` announcement
+    if cap_changed {
+        let mut cap_reached = state.cap_reached.write().await;
+        *cap_reached = false;
+    }
+}
+
+/// Returns the configuration stored in `config.toml`, or `None` if it was never saved.
+///
+/// This can differ from `get_config` when the effective config was not loaded from disk.
+#[tauri::command]
+async fn get_persisted_config() -> Result<Option<config::Config>, String> {
+    match config::Config::path() {
+        Some(path) if path.exists() => config::Config::load().map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Shows or hides a source's stat tile on overlays and saves it. Scoring is unaffected;
+/// overlays pick the change up on their next load.
+#[tauri::command]
+async fn set_source_visible(
+    source: String,
+    visible: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let mut config = state.config.write().await;
+    // Save first so the running config never differs from what is on disk
+    let mut updated = config.clone();
+    updated.overlay.visible.set(&source, visible)?;
+    updated.save()?;
+    *config = updated;
     Ok(())
 }
 
+/// Sets the overlay header title and saves it to `config.toml`.
 #[tauri::command]
-async fn get_points(state: State<'_, Arc<AppState>>) -> Result<points::PointState, String> {
-    let points = state.points.read().await;
-    Ok(points.clone())
+async fn set_overlay_title(title: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    let title = title.trim();
+    let mut config = state.config.write().await;
+    // Save first so the running config never differs from what is on disk
+    let mut updated = config.clone();
+    updated.overlay.title = if title.is_empty() {
+        config::OverlayConfig::default().title
+    } else {
+        title.to_string()
+    };
+    updated.save()?;
+    *config = updated;
+    Ok(())
 }
 
+/// Sets whether manual and visitor points count toward goal progress and saves it.
 #[tauri::command]
-async fn reset_points(
+async fn set_goal_counts_manual(
+    enabled: bool,
     state: State<'_, Arc<AppState>>,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
-    // Reset points
     {
-        let mut points = state.points.write().await;
-        *points = points::PointState::default();
+        let mut config = state.config.write().await;
+        config.overlay.goal_counts_manual = enabled;
+        config.save()?;
     }
+    emit_points(&state, &app).await;
+    Ok(())
+}
 
-    // Reset raw metrics (keep initial_subscribers)
-    {
-        let mut metrics = state.raw_metrics.write().await;
-        let initial_subs = metrics.initial_subscribers;
-        *metrics = points::RawMetrics {
-            initial_subscribers: initial_subs,
-            current_subscribers: initial_subs,
-            ..Default::default()
-        };
+/// Sets which metric the progress bar tracks and its goal (in that metric's unit), and saves both.
+#[tauri::command]
+async fn set_progress_metric(
+    metric: config::ProgressMetric,
+    goal: i64,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if goal <= 0 {
+        return Err("Goal must be greater than 0".into());
     }
-
-    // Reset concurrent bonus flag
     {
-        let mut bonus_given = state.concurrent_bonus_given.write().await;
-        *bonus_given = false;
+        let mut config = state.config.write().await;
+        config.overlay.progress_metric = metric;
+        config.overlay.goal = goal;
+        config.save()?;
     }
+    let _ = app.emit("goal-changed", goal);
+    emit_points(&state, &app).await;
+    Ok(())
+}
 
-    let points = state.points.read().await.clone();
-    let metrics = state.raw_metrics.read().await.clone();
-    println!("Points reset");
-
-    let payload = PointsUpdatePayload {
-        points: points.clone(),
-        metrics: metrics.clone(),
-        config: config::POINTS_CONFIG.clone(),
-    };
-    let _ = app.emit("points-update", &payload);
+/// Returns whether overlays play the flash and celebration effects by default.
+#[tauri::command]
+async fn get_superchat_effects(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.config.read().await.overlay.superchat_effects)
+}
 
-    // Broadcast to web clients
-    let _ = state.web_broadcast.send(PointsPayload {
-        points,
-        metrics,
-        config: config::POINTS_CONFIG.clone(),
-    });
+/// Sets whether overlays play the flash and celebration effects by default and saves it.
+/// Applies to overlays loaded afterwards; an `effects` query param still overrides it.
+#[tauri::command]
+async fn set_superchat_effects(
+    enabled: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let mut config = state.config.write().await;
+    config.overlay.superchat_effects = enabled;
+    config.save()
+}
 
+/// Sets the progress bar goal, saves it, and pushes it to every overlay.
+#[tauri::command]
+async fn set_goal(
+    goal: i64,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if goal <= 0 {
+        return Err("Goal must be greater than 0".into());
+    }
+    {
+        let mut config = state.config.write().await;
+        config.overlay.goal = goal;
+        config.save()?;
+    }
+    let _ = app.emit("goal-changed", goal);
+    emit_points(&state, &app).await;
     Ok(())
 }
 
+/// Sets the stretch goals (ascending, in the progress metric's unit), saves them, and pushes
+/// them to every overlay. An empty list goes back to the single `goal`.
 #[tauri::command]
-async fn open_viewer_window(app: tauri::AppHandle) -> Result<(), String> {
-    let _viewer = WebviewWindowBuilder::new(
-        &app,
-        "viewer",
-        tauri::WebviewUrl::App("/viewer.html".into()),
-    )
-    .title("YT Point - 視聴者用表示")
-    .inner_size(800.0, 350.0)
-    .transparent(true)
-    .decorations(true)
-    .always_on_top(true)
-    .build()
-    .map_err(|e| e.to_string())?;
-
+async fn set_goals(
+    goals: Vec<i64>,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if goals.iter().any(|goal| *goal <= 0) {
+        return Err("Goals must be greater than 0".into());
+    }
+    if goals.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err("Goals must be in ascending order".into());
+    }
+    let goal_list = {
+        let mut config = state.config.write().await;
+        config.overlay.goals = goals;
+        config.save()?;
+        config.overlay.goal_list()
+    };
+    let _ = app.emit("goals-changed", &goal_list);
+    emit_points(&state, &app).await;
     Ok(())
 }
 
+/// Sets how long the overlay's count-up animation takes, saves it, and pushes it (with the
+/// other live overlay options) to every connected overlay without a reload.
 #[tauri::command]
-async fn open_youtube_login(app: tauri::AppHandle) -> Result<(), String> {
-    // Close existing window if any
-    if let Some(window) = app.get_webview_window("youtube-login") {
-        let _ = window.close();
-        // Wait a bit for the window to close
-        tokio::time::sleep(Duration::from_millis(100)).await;
+async fn set_overlay_animation(
+    duration_ms: u64,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if duration_ms > config::MAX_ANIMATION_DURATION_MS {
+        return Err(format!(
+            "Animation duration must be at most {} ms",
+            config::MAX_ANIMATION_DURATION_MS
+        ));
     }
-
-    // Create new window
-    let login_window = WebviewWindowBuilder::new(
-        &app,
-        "youtube-login",
-        tauri::WebviewUrl::External("https://studio.youtube.com".parse().unwrap()),
-    )
-    .title("YouTube Login")
-    .inner_size(1000.0, 700.0)
-    .build()
-    .map_err(|e| e.to_string())?;
-
-    // Listen for window close and check login status
-    let app_handle = app.clone();
-    login_window.on_window_event(move |event| {
-        if let tauri::WindowEvent::CloseRequested { .. } = event {
-            let app = app_handle.clone();
-            tauri::async_runtime::spawn(async move {
-                // Use main window to check cookies (cookie store is shared across windows)
-                if let Some(window) = app.get_webview_window("main") {
-                    let url: url::Url = "https://www.youtube.com".parse().unwrap();
-                    if let Ok(cookies) = window.cookies_for_url(url) {
-                        let has_sapisid = cookies.iter().any(|c| c.name() == "SAPISID");
-                        let has_secure_3psid = cookies.iter().any(|c| c.name() == "__Secure-3PSID");
-                        let is_logged_in = has_sapisid && has_secure_3psid;
-                        let _ = app.emit("youtube-login-status", is_logged_in);
-                    }
-                }
-            });
-        }
-    });
-
+    let live_config = {
+        let mut config = state.config.write().await;
+        config.overlay.animation_duration_ms = duration_ms;
+        config.save()?;
+        web_server::OverlayLiveConfig::new(&config.overlay)
+    };
+    let _ = app.emit("overlay-config", &live_config);
+    send_web_event(&state, WebEvent::OverlayConfig(live_config)).await;
     Ok(())
 }
 
+/// Sets the soft cap on the total points (`None` removes it).
 #[tauri::command]
-async fn get_youtube_cookies(app: tauri::AppHandle) -> Result<String, String> {
-    // Use youtube-login window if it exists, otherwise use main window
-    // (avoids creating a hidden YouTube page that crashes WebKit on WSL2)
-    let window = app
-        .get_webview_window("youtube-login")
-        .or_else(|| app.get_webview_window("main"))
-        .ok_or("No window available")?;
-
-    // Use Tauri's cookies_for_url API to get all cookies including HTTP-only ones
-    // Cookie store is shared across all windows, so any window can be used
-    let url: url::Url = "https://www.youtube.com".parse().unwrap();
-    let cookies: Vec<Cookie<'_>> = window
-        .cookies_for_url(url)
-        .map_err(|e| format!("Failed to get cookies: {}", e))?;
-
-    // Format cookies as a string
-    let cookie_str: String = cookies
-        .iter()
-        .map(|c| format!("{}={}", c.name(), c.value()))
-        .collect::<Vec<_>>()
-        .join("; ");
-
-    println!("Retrieved {} cookies", cookies.len());
-
-    // Check for auth cookies
-    let has_sapisid = cookies.iter().any(|c| c.name() == "SAPISID");
-    let has_secure_3psid = cookies.iter().any(|c| c.name() == "__Secure-3PSID");
-
-    if has_sapisid && has_secure_3psid {
-        println!("Auth cookies found: SAPISID and __Secure-3PSID");
-    } else {
-        println!("Warning: Some auth cookies may be missing");
-        println!("  SAPISID: {}", has_sapisid);
-        println!("  __Secure-3PSID: {}", has_secure_3psid);
+async fn set_total_cap(
+    cap: Option<i64>,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if cap.is_some_and(|cap| cap <= 0) {
+        return Err("Total cap must be greater than 0".into());
     }
-
-    Ok(cookie_str)
+    {
+        let mut config = state.config.write().await;
+        config.points.total_cap = cap;
+        config.save()?;
+    }
+    // A new cap gets its own `cap-reached` announcement
+    {
+        let mut cap_reached = state.cap_reached.write().await;
+        *cap_reached = false;
+    }
+    emit_points(&state, &app).await;
+    Ok(())
 }
 
+/// Longest overlay message accepted, in characters
+const OVERLAY_MESSAGE_MAX_CHARS: usize = 200;
+
+/// Shows a temporary banner on every connected overlay.
 #[tauri::command]
-async fn get_server_url(state: State<'_, Arc<AppState>>) -> Result<Option<String>, String> {
-    let url = state.server_url.read().await;
-    Ok(url.clone())
+async fn broadcast_overlay_message(
+    text: String,
+    duration_ms: u64,
+    state: State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    // Single line of plain text; overlays render it with textContent
+    let text: String = text
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(OVERLAY_MESSAGE_MAX_CHARS)
+        .collect();
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("Message is empty".into());
+    }
+
+    let message = OverlayMessage {
+        text,
+        duration_ms: duration_ms.clamp(500, 60_000),
+    };
+    let _ = app.emit("overlay-message", &message);
+    send_web_event(&state, WebEvent::Message(message)).await;
+    Ok(())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Create broadcast channel for web clients
-    let (web_tx, _) = broadcast::channel::<PointsPayload>(16);
-
-    let app_state = Arc::new(AppState {
-        is_monitoring: RwLock::new(false),
-        points: RwLock::new(points::PointState::default()),
-        sidecar: RwLock::new(None),
-        raw_metrics: RwLock::new(points::RawMetrics::default()),
-        monitoring_video_id: RwLock::new(None),
-        monitoring_channel_id: RwLock::new(None),
-        is_authenticated: RwLock::new(false),
-        web_broadcast: web_tx.clone(),
-        server_url: RwLock::new(None),
-        concurrent_bonus_given: RwLock::new(false),
+    let config = config::Config::load().unwrap_or_else(|e| {
+        eprintln!("Using default config: {}", e);
+        config::Config::default()
     });
+    // Points of a session that did not stop cleanly, for resume_last_session
+    let saved_session = state::SessionState::load().unwrap_or_default();
+    if saved_session.video_id.is_some() {
+        println!(
+            "Restored points of the last session: {}",
+            saved_session.points.total
+        );
+    }
+
+    let app_state = Arc::new(AppState::new(config, saved_session));
 
     let app_state_clone = app_state.clone();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .manage(app_state)
-        .setup(move |_app| {
+        .setup(move |app| {
+            // Start heartbeat so the UI can detect a hung backend
+            tauri::async_runtime::spawn(run_heartbeat(
+                app_state_clone.clone(),
+                app.handle().clone(),
+            ));
+
             // Start web server
             let state = app_state_clone.clone();
+            let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                if let Some(server) = web_server::WebServer::new(web_tx) {
+                let preferred_port = state.config.read().await.server.port;
+                if let Some(server) =
+                    web_server::WebServer::new(state.clone(), app_handle, preferred_port)
+                {
                     let url = server.url();
                     println!("Starting OBS viewer server at {}", url);
                     {
@@ -629,46 +3792,103 @@ pub fn run() {
                         eprintln!("Failed to start web server: {}", e);
                     }
                 } else {
-                    eprintln!("Failed to find available port for web server (1420-1450)");
+                    eprintln!("Failed to find available port for web server (1430-1460)");
                 }
             });
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             start_monitoring,
+            parse_video_id,
             stop_monitoring,
+            force_stop,
+            pause_monitoring,
+            resume_monitoring,
+            start_demo,
+            stop_and_reset,
+            set_monitoring_channel,
             add_manual_points,
             add_visitor_points,
             add_subscriber_points,
             get_points,
+            get_source_progress,
+            get_data_freshness,
+            get_session_diagnostics,
+            benchmark_poll,
+            get_session_analytics,
+            estimate_time_to_goal,
+            get_history,
+            clear_history,
+            export_history_csv,
+            get_history_downsampled,
+            get_last_session_summary,
+            save_session_now,
+            resume_last_session,
+            add_marker,
+            get_markers,
+            list_stored_data,
+            clear_stored_data,
+            list_sessions,
+            load_session_summary,
             reset_points,
+            get_active_modifiers,
+            get_superchat_stats,
+            get_last_superchat_timestamp,
+            get_using_exact_subscribers,
+            clear_superchat_data,
             open_viewer_window,
+            reset_viewer_window_layout,
             open_youtube_login,
             get_youtube_cookies,
+            reauthenticate,
+            refresh_sidecar,
+            prewarm_sidecar,
+            reload_sidecar,
+            get_sidecar_status,
+            get_streams,
+            get_auth_status,
+            logout,
+            get_stream_info,
+            import_cookies_from_file,
             get_server_url,
+            get_broadcast_stats,
+            export_viewer_html,
+            list_overlay_layouts,
+            get_network_exposure,
+            verify_viewer_reachable,
+            get_config,
+            set_config,
+            get_persisted_config,
+            set_overlay_title,
+            set_goal_counts_manual,
+            set_goal,
+            set_goals,
+            set_overlay_animation,
+            set_progress_metric,
+            get_superchat_effects,
+            set_superchat_effects,
+            set_source_visible,
+            set_total_cap,
+            broadcast_overlay_message,
+            dump_state,
+            self_test_pipeline,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|app, event| match event {
+            // If main window is closed, exit the app
             tauri::RunEvent::WindowEvent {
                 label,
                 event: tauri::WindowEvent::CloseRequested { .. },
                 ..
-            } => {
-                // If main window is closed, exit the app
-                if label == "main" {
-                    let state = app.state::<Arc<AppState>>();
-                    let state = state.inner().clone();
-                    tauri::async_runtime::block_on(async move {
-                        // Stop sidecar if running
-                        let mut sidecar_guard = state.sidecar.write().await;
-                        if let Some(mut sidecar) = sidecar_guard.take() {
-                            let _ = sidecar.stop().await;
-                            println!("Sidecar stopped on exit");
-                        }
-                    });
-                    std::process::exit(0);
-                }
+            } if label == "main" => {
+                let state = app.state::<Arc<AppState>>();
+                let state = state.inner().clone();
+                tauri::async_runtime::block_on(async move {
+                    // Stop sidecars if running
+                    stop_all_sidecars(&state).await;
+                });
+                std::process::exit(0);
             }
             tauri::RunEvent::ExitRequested { api, .. } => {
                 // Don't prevent exit
@@ -679,14 +3899,315 @@ pub fn run() {
                 let state = app.state::<Arc<AppState>>();
                 let state = state.inner().clone();
                 tauri::async_runtime::block_on(async move {
-                    // Stop sidecar if running
-                    let mut sidecar_guard = state.sidecar.write().await;
-                    if let Some(mut sidecar) = sidecar_guard.take() {
-                        let _ = sidecar.stop().await;
-                        println!("Sidecar stopped on exit");
-                    }
+                    // Stop sidecars if running
+                    stop_all_sidecars(&state).await;
                 });
             }
             _ => {}
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_state() -> Arc<AppState> {
+        Arc::new(AppState::new(
+            config::Config::default(),
+            state::SessionState::default(),
+        ))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn manual_points_survive_concurrent_recalculation() {
+        let state = test_state();
+        state.raw_metrics.write().await.like_count = 100;
+        let manual_rate = state.config.read().await.points.manual_rate;
+
+        let mut tasks = Vec::new();
+        for _ in 0..50 {
+            let adding = state.clone();
+            tasks.push(tokio::spawn(async move {
+                add_manual(&adding, 1).await;
+            }));
+            let recalculating = state.clone();
+            tasks.push(tokio::spawn(async move {
+                recalculate_points(&recalculating).await;
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let points = state.points.read().await.clone();
+        assert_eq!(points.manual, 50);
+        assert_eq!(
+            points.total,
+            points.likes + (50.0 * manual_rate) as i64,
+            "{:?}",
+            points
+        );
+    }
+
+    #[tokio::test]
+    async fn milestones_are_awarded_again_after_reset() {
+        let state = test_state();
+        state.config.write().await.points.milestones = vec![
+            config::Milestone {
+                threshold_yen: 1000,
+                bonus_points: 10,
+            },
+            config::Milestone {
+                threshold_yen: 5000,
+                bonus_points: 50,
+            },
+        ];
+
+        state.raw_metrics.write().await.superchat_amount = 6000;
+        let (points, _, _, new_milestones) = recalculate_points(&state).await;
+        assert_eq!(new_milestones.len(), 2);
+        assert_eq!(points.bonus, 60);
+
+        let (points, _, _, new_milestones) = recalculate_points(&state).await;
+        assert!(new_milestones.is_empty());
+        assert_eq!(points.bonus, 60);
+
+        clear_points(&state).await;
+        state.raw_metrics.write().await.superchat_amount = 6000;
+        let (points, _, _, new_milestones) = recalculate_points(&state).await;
+        assert_eq!(new_milestones.len(), 2);
+        assert_eq!(points.bonus, 60);
+    }
+
+    fn saved_session() -> state::SessionState {
+        state::SessionState {
+            video_id: Some("abc".to_string()),
+            metrics: Some(points::RawMetrics {
+                superchat_amount: 6000,
+                initial_subscribers: 1000,
+                ..Default::default()
+            }),
+            concurrent_bonus_given: true,
+            markers: vec![history::Marker {
+                elapsed_seconds: 60,
+                label: "opening".to_string(),
+                created_at: 0,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn startup_restores_saved_metrics_and_bonus() {
+        let state = AppState::new(config::Config::default(), saved_session());
+        assert_eq!(state.raw_metrics.read().await.superchat_amount, 6000);
+        assert!(*state.concurrent_bonus_given.read().await);
+        assert_eq!(state.markers.read().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn resumed_session_keeps_markers_and_history() {
+        let state = test_state();
+        let saved = saved_session();
+        record_snapshot(
+            &state,
+            &points::PointState::default(),
+            &points::RawMetrics::default(),
+        )
+        .await;
+
+        reset_session(&state, "abc", false, Some(&saved)).await;
+        assert_eq!(state.markers.read().await.len(), 1);
+        assert_eq!(state.history.read().await.len(), 1);
+        assert!(*state.concurrent_bonus_given.read().await);
+
+        // A new session starts empty
+        reset_session(&state, "xyz", false, None).await;
+        assert!(state.markers.read().await.is_empty());
+        assert!(state.history.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_startup_step_stops_sidecar() {
+        let mut harness = sidecar::tests::Harness::new().await;
+        let mut sidecar = std::mem::take(&mut harness.manager);
+
+        let responder = async {
+            let requests = harness.wait_for_requests(1).await;
+            assert_eq!(requests[0]["method"], "getLiveInfo");
+            harness.respond(
+                requests[0]["id"].as_u64().unwrap(),
+                serde_json::json!({ "channelId": "UC123", "isLive": true }),
+            );
+
+            let requests = harness.wait_for_requests(2).await;
+            assert_eq!(requests[1]["method"], "getSubscriberCount");
+            harness.respond_error(requests[1]["id"].as_u64().unwrap(), "channel not found");
+
+            // Stopping asks the sidecar to leave the chat before killing it
+            let requests = harness.wait_for_requests(3).await;
+            assert_eq!(requests[2]["method"], "stopLiveChat");
+            harness.respond(requests[2]["id"].as_u64().unwrap(), serde_json::Value::Null);
+        };
+        let (result, ()) = tokio::join!(
+            prepare_sidecar(&mut sidecar, "dQw4w9WgXcQ", None, Some(false), true),
+            responder
+        );
+
+        assert_eq!(result.err().as_deref(), Some("channel not found"));
+        assert!(!sidecar.is_running().await);
+        // Live chat was never started
+        assert_eq!(harness.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn failed_kill_still_stops_other_sidecars() {
+        let live_info: sidecar::LiveInfo =
+            serde_json::from_value(serde_json::json!({ "channelId": "UC123", "isLive": true }))
+                .unwrap();
+        let mut failing = sidecar::tests::Harness::failing_kill("kill failed").await;
+        let mut working = sidecar::tests::Harness::new().await;
+        let streams: Vec<Arc<StreamSession>> = [&mut failing, &mut working]
+            .into_iter()
+            .enumerate()
+            .map(|(index, harness)| {
+                Arc::new(StreamSession::new(
+                    format!("video{}", index),
+                    None,
+                    live_info.clone(),
+                    std::mem::take(&mut harness.manager),
+                    points::RawMetrics::default(),
+                    index == 0,
+                ))
+            })
+            .collect();
+
+        let result = stop_sidecars(&streams, true).await;
+
+        assert_eq!(result.err().as_deref(), Some("kill failed"));
+        for stream in &streams {
+            assert!(stream.sidecar.read().await.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn reset_while_paused_resumes_from_clean_state() {
+        let state = test_state();
+        *state.is_monitoring.write().await = true;
+        {
+            let mut metrics = state.raw_metrics.write().await;
+            metrics.superchat_amount = 5000;
+            metrics.concurrent_viewers = config::CONCURRENT_BONUS_THRESHOLD + 1;
+            metrics.initial_subscribers = 1000;
+        }
+        state.points.write().await.manual = 3;
+        recalculate_points(&state).await;
+        assert!(*state.concurrent_bonus_given.read().await);
+
+        set_paused(&state, true).await.unwrap();
+        assert!(set_paused(&state, true).await.is_err());
+        clear_points(&state).await;
+        set_paused(&state, false).await.unwrap();
+        let (points, metrics, ..) = recalculate_points(&state).await;
+
+        assert!(!*state.is_paused.read().await);
+        assert_eq!(points.total, 0);
+        assert_eq!(points.manual, 0);
+        assert_eq!(metrics.superchat_amount, 0);
+        assert_eq!(metrics.initial_subscribers, 1000);
+        assert!(!*state.concurrent_bonus_given.read().await);
+    }
+
+    #[tokio::test]
+    async fn new_rates_apply_to_current_metrics() {
+        let state = test_state();
+        {
+            let mut metrics = state.raw_metrics.write().await;
+            metrics.superchat_amount = 10_000;
+            metrics.like_count = 50;
+        }
+        let mut new_config = config::Config::default();
+        new_config.points.superchat_rate = 20.0;
+        new_config.points.like_rate = 5.0;
+        new_config.points.total_cap = Some(100);
+        *state.cap_reached.write().await = true;
+
+        replace_config(&state, new_config).await;
+        let (points, ..) = recalculate_points(&state).await;
+
+        assert_eq!(points.superchat, 500);
+        assert_eq!(points.likes, 10);
+        assert_eq!(points.total, 100);
+        // The new cap is announced again
+        assert!(!*state.cap_reached.read().await);
+    }
+
+    #[tokio::test]
+    async fn self_test_leaves_live_state_alone() {
+        let state = test_state();
+        let mut rx = state.web_broadcast.subscribe();
+
+        let stages = run_self_test(config::Config::default()).await;
+
+        assert!(
+            stages.iter().all(|stage| stage.passed),
+            "{:?}",
+            stages
+                .iter()
+                .map(|stage| (stage.stage, &stage.detail))
+                .collect::<Vec<_>>()
+        );
+        assert!(rx.try_recv().is_err());
+        assert!(state.latest_points.read().await.is_none());
+        assert_eq!(state.points.read().await.total, 0);
+    }
+
+    #[test]
+    fn disabled_concurrent_source_lists_no_bonus() {
+        let mut config = config::Config::default();
+        assert!(matches!(
+            active_modifiers(&config, true).as_slice(),
+            [ActiveModifier::ConcurrentBonus { reached: true, .. }, ..]
+        ));
+
+        config.points.concurrent_enabled = false;
+        assert!(
+            !active_modifiers(&config, true)
+                .iter()
+                .any(|modifier| matches!(modifier, ActiveModifier::ConcurrentBonus { .. }))
+        );
+    }
+
+    #[test]
+    fn reaching_total_cap_completes_goal() {
+        let points_config = config::PointsConfig {
+            total_cap: Some(100),
+            ..Default::default()
+        };
+        let mut points = points::PointState {
+            total: 130,
+            superchat: 80,
+            manual: 50,
+            ..Default::default()
+        };
+        points.apply_cap(points_config.total_cap);
+
+        assert!(goal_points(&points, &points_config, true, 500) >= 500);
+        // Even when manual points are excluded from the goal
+        assert!(goal_points(&points, &points_config, false, 500) >= 500);
+    }
+
+    #[test]
+    fn goal_below_cap_uses_points() {
+        let points_config = config::PointsConfig {
+            total_cap: Some(100),
+            ..Default::default()
+        };
+        let points = points::PointState {
+            total: 99,
+            superchat: 99,
+            ..Default::default()
+        };
+        assert_eq!(goal_points(&points, &points_config, true, 500), 99);
+    }
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 
-use crate::config::PointsConfig;
+use crate::config::{
+    BASELINE_PLAUSIBLE_RATIO, CONCURRENT_BONUS_POINTS, CurrencyConfig, PointsConfig,
+    RECENT_SUPERCHATS_LIMIT, VIEWER_DIP_MIN_PREVIOUS, VIEWER_DIP_RATIO,
+};
+use crate::sidecar::SuperchatEventData;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PointState {
@@ -18,6 +23,12 @@ pub struct PointState {
     pub manual: i64,
     /// ライバー訪問
     pub visitor: i64,
+    /// メンバーシップ加入からのポイント
+    #[serde(default)]
+    pub membership: i64,
+    /// スーパーチャットのマイルストーン達成ボーナス
+    #[serde(default)]
+    pub bonus: i64,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -28,33 +39,871 @@ pub struct RawMetrics {
     pub concurrent_viewers: i64,
     /// 高評価数
     pub like_count: i64,
+    /// ポイント計算に使う高評価数（`metrics.clamp_negative_likes` が有効ならセッション中の最大値）
+    #[serde(default)]
+    pub like_high: i64,
     /// 配信開始時のチャンネル登録者数
     pub initial_subscribers: i64,
     /// 現在のチャンネル登録者数
     pub current_subscribers: i64,
+    /// メンバーシップ加入数（ギフトは贈られた人数分）
+    #[serde(default)]
+    pub membership_count: i64,
+    /// 表示専用の追加指標（サイドカーが任意に返す。ポイント計算には使わない）
+    #[serde(default)]
+    pub extra_metrics: HashMap<String, i64>,
+}
+
+impl RawMetrics {
+    /// 複数の配信の指標を合計する（追加指標は名前ごとに合計する）
+    pub fn combine<'a>(all: impl IntoIterator<Item = &'a RawMetrics>) -> Self {
+        let mut combined = Self::default();
+        for metrics in all {
+            combined.superchat_amount += metrics.superchat_amount;
+            combined.concurrent_viewers += metrics.concurrent_viewers;
+            combined.like_count += metrics.like_count;
+            combined.like_high += metrics.like_high;
+            combined.initial_subscribers += metrics.initial_subscribers;
+            combined.current_subscribers += metrics.current_subscribers;
+            combined.membership_count += metrics.membership_count;
+            for (name, value) in &metrics.extra_metrics {
+                *combined.extra_metrics.entry(name.clone()).or_default() += value;
+            }
+        }
+        combined
+    }
+
+    /// 中断前のセッションから、開始時の登録者数と配信中に積み上がった値を引き継ぐ
+    ///
+    /// 同接数・高評価数・現在の登録者数は再開時に取得した値のままにする。
+    pub fn resume_from(&mut self, saved: &RawMetrics) {
+        if saved.initial_subscribers > 0 {
+            self.initial_subscribers = saved.initial_subscribers;
+        }
+        self.superchat_amount = saved.superchat_amount;
+        self.membership_count = saved.membership_count;
+    }
+
+    /// 開始時の登録者数が、後から得た値に比べて信用できないかどうか
+    pub fn has_implausible_baseline(&self, current_subscribers: i64) -> bool {
+        current_subscribers > 0
+            && (self.initial_subscribers <= 0
+                || (self.initial_subscribers as f64)
+                    < current_subscribers as f64 * BASELINE_PLAUSIBLE_RATIO)
+    }
+
+    /// 新しい同時接続者数が、直前の値からの不自然な急落に見えるかどうか
+    pub fn is_viewer_dip(&self, viewers: i64) -> bool {
+        self.concurrent_viewers >= VIEWER_DIP_MIN_PREVIOUS
+            && (viewers as f64) < self.concurrent_viewers as f64 * VIEWER_DIP_RATIO
+    }
+
+    /// 新しい高評価数を反映する
+    ///
+    /// 非公開で `None` の場合は直前の値を保つ（0 に落とさない）。`clamp_negative` が有効なら
+    /// `like_high` にセッション中の最大値を保ち、高評価が取り消されてもポイントを減らさない。
+    /// `like_count` は常に取得した値のまま。
+    pub fn update_likes(&mut self, likes: Option<i64>, clamp_negative: bool) {
+        let Some(likes) = likes else {
+            return;
+        };
+        self.like_high = if clamp_negative {
+            self.points_like_count().max(likes)
+        } else {
+            likes
+        };
+        self.like_count = likes;
+    }
+
+    /// ポイント計算に使う高評価数（`like_high` が未更新なら `like_count`）
+    pub fn points_like_count(&self) -> i64 {
+        self.like_high.max(self.like_count)
+    }
+
+    /// 新しい同時接続者数を反映する。急落として保留した場合は `false`
+    ///
+    /// `dip_pending` は直前の値を保留したかどうか。保留は続けて 1 回までで、
+    /// 次の取得でも低いままならその値を採用する。
+    pub fn update_viewers(
+        &mut self,
+        viewers: i64,
+        suppress_dips: bool,
+        dip_pending: &mut bool,
+    ) -> bool {
+        if suppress_dips && !*dip_pending && self.is_viewer_dip(viewers) {
+            *dip_pending = true;
+            return false;
+        }
+        *dip_pending = false;
+        self.concurrent_viewers = viewers;
+        true
+    }
+}
+
+/// スーパーチャットの金額を基準通貨に換算する
+///
+/// 未知の通貨は警告を出し、`fallback_rate` で換算する。端数は切り捨てる。
+pub fn normalize_amount(amount: i64, currency: &str, config: &CurrencyConfig) -> i64 {
+    let rate = config.rate(currency).unwrap_or_else(|| {
+        eprintln!(
+            "Unknown superchat currency {:?}; using fallback rate {}",
+            currency, config.fallback_rate
+        );
+        config.fallback_rate
+    });
+    (amount as f64 * rate) as i64
+}
+
+/// 上限が設定されていれば `points` をそれ以下に抑える
+pub fn clamp_to_cap(points: i64, cap: Option<i64>) -> i64 {
+    cap.map_or(points, |cap| points.min(cap))
+}
+
+/// 有効なソースのポイントを上限で抑える（無効なら 0）
+fn source_points(enabled: bool, points: i64, cap: Option<i64>) -> i64 {
+    if enabled {
+        clamp_to_cap(points, cap)
+    } else {
+        0
+    }
+}
+
+/// 指標以外からポイントになる値（手入力の値とセッション中に付与したボーナス）
+#[derive(Debug, Clone, Default)]
+pub struct PointInputs {
+    /// 手動入力の新規登録者数
+    pub subscribers: i64,
+    /// 埼玉ボーナスのカウント
+    pub manual: i64,
+    /// ライバー訪問の人数
+    pub visitor: i64,
+    /// 同接ボーナスを付与済みか
+    pub concurrent_bonus_given: bool,
+    /// 達成済みのマイルストーンのボーナスの合計
+    pub milestone_bonus: i64,
+}
+
+impl PointInputs {
+    /// 保存されているポイントから手入力の値を引き継ぐ
+    pub fn from_counts(points: &PointState) -> Self {
+        Self {
+            subscribers: points.subscribers,
+            manual: points.manual,
+            visitor: points.visitor,
+            ..Default::default()
+        }
+    }
 }
 
 impl PointState {
-    pub fn calculate_from_metrics(metrics: &RawMetrics, config: &PointsConfig) -> Self {
-        let superchat = (metrics.superchat_amount as f64 / config.superchat_rate) as i64;
-        let concurrent = (metrics.concurrent_viewers as f64 / config.concurrent_rate) as i64;
-        let likes = (metrics.like_count as f64 / config.like_rate) as i64;
-        let new_subscribers = metrics.current_subscribers - metrics.initial_subscribers;
-        let subscribers = (new_subscribers as f64 / config.subscriber_rate) as i64;
+    /// 指標と手入力の値から項目ごとのポイントと合計を計算する
+    ///
+    /// 項目ごとの上限と無効にした項目（0 になる）はここで適用する。合計の上限は
+    /// `apply_cap` で別に適用する。`subscribers` には手動入力の人数をそのまま入れる。
+    pub fn calculate(metrics: &RawMetrics, inputs: &PointInputs, config: &PointsConfig) -> Self {
+        let superchat = source_points(
+            config.superchat_enabled,
+            (metrics.superchat_amount as f64 / config.superchat_rate) as i64,
+            config.superchat_cap,
+        );
+        let concurrent = source_points(
+            config.concurrent_enabled && inputs.concurrent_bonus_given,
+            CONCURRENT_BONUS_POINTS,
+            config.concurrent_cap,
+        );
+        let likes = source_points(
+            config.like_enabled,
+            (metrics.points_like_count() as f64 / config.like_rate) as i64,
+            config.like_cap,
+        );
+        let subscriber_points = source_points(
+            config.subscriber_enabled,
+            (inputs.subscribers as f64 / config.subscriber_rate) as i64,
+            config.subscriber_cap,
+        );
+        let membership = (metrics.membership_count as f64 * config.membership_rate) as i64;
+        let manual_points = (inputs.manual as f64 * config.manual_rate) as i64;
+        let visitor_points = (inputs.visitor as f64 * config.visitor_rate) as i64;
 
         Self {
-            total: superchat + concurrent + likes + subscribers,
+            total: superchat
+                + concurrent
+                + likes
+                + subscriber_points
+                + membership
+                + inputs.milestone_bonus
+                + manual_points
+                + visitor_points,
             superchat,
             concurrent,
             likes,
-            subscribers,
-            manual: 0,
-            visitor: 0,
+            subscribers: inputs.subscribers,
+            manual: inputs.manual,
+            visitor: inputs.visitor,
+            membership,
+            bonus: inputs.milestone_bonus,
+        }
+    }
+
+    /// 目標の進捗に数えるポイント
+    ///
+    /// `counts_manual` が `false` の場合、手動ポイントとライバー訪問の分を合計から除く。
+    pub fn goal_points(&self, config: &PointsConfig, counts_manual: bool) -> i64 {
+        if counts_manual {
+            return self.total;
+        }
+        self.total
+            - (self.manual as f64 * config.manual_rate) as i64
+            - (self.visitor as f64 * config.visitor_rate) as i64
+    }
+
+    /// 合計ポイントを上限で切り詰める（内訳はそのまま）。切り詰めたら `true`
+    pub fn apply_cap(&mut self, cap: Option<i64>) -> bool {
+        match cap {
+            Some(cap) if self.total >= cap => {
+                self.total = cap;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// ソースごとの次の 1 ポイントまでの進み具合
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceProgress {
+    /// `superchat` / `likes` / `subscribers`
+    pub source: &'static str,
+    /// 元の値（円、件、人）
+    pub value: i64,
+    /// レート（値 / ポイント）
+    pub rate: f64,
+    /// ポイントに換算されずに残っている値
+    pub remainder: f64,
+    /// 次の 1 ポイントまでの割合（0.0〜1.0 未満）
+    pub progress: f64,
+}
+
+impl SourceProgress {
+    fn new(source: &'static str, value: i64, rate: f64) -> Self {
+        // Points are floored, so whatever is left over is progress toward the next one
+        let remainder = if rate > 0.0 {
+            (value as f64).rem_euclid(rate)
+        } else {
+            0.0
+        };
+        Self {
+            source,
+            value,
+            rate,
+            remainder,
+            progress: if rate > 0.0 { remainder / rate } else { 0.0 },
         }
     }
 
-    pub fn add_manual(&mut self, amount: i64) {
-        self.manual += amount;
-        self.total += amount;
+    /// レートで割ってポイントにするソースの進み具合
+    ///
+    /// 同時接続者数は一度きりのボーナス、手動ポイントとライバー訪問は掛け算なので含めない。
+    /// 新規登録者は手動入力の値を使う。
+    pub fn all(metrics: &RawMetrics, points: &PointState, config: &PointsConfig) -> Vec<Self> {
+        vec![
+            Self::new("superchat", metrics.superchat_amount, config.superchat_rate),
+            Self::new("likes", metrics.points_like_count(), config.like_rate),
+            Self::new("subscribers", points.subscribers, config.subscriber_rate),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SuperchatStats {
+    /// 投稿者ごとの累計金額
+    pub leaderboard: HashMap<String, i64>,
+    /// 直近のスーパーチャット（新しい順）
+    pub recent: VecDeque<SuperchatEventData>,
+    /// 通貨ごとの累計金額
+    pub by_currency: HashMap<String, i64>,
+}
+
+impl SuperchatStats {
+    pub fn record(&mut self, superchat: &SuperchatEventData) {
+        *self
+            .leaderboard
+            .entry(superchat.author.clone())
+            .or_default() += superchat.amount;
+        *self
+            .by_currency
+            .entry(superchat.currency.clone())
+            .or_default() += superchat.amount;
+
+        self.recent.push_front(superchat.clone());
+        self.recent.truncate(RECENT_SUPERCHATS_LIMIT);
+    }
+}
+
+/// セッション集計用のカウンタ
+#[derive(Debug, Clone, Default)]
+pub struct SessionCounters {
+    /// 同時接続者数の取得値の合計
+    pub viewer_sum: i64,
+    /// 同時接続者数の取得回数
+    pub viewer_samples: u64,
+    /// 同時接続者数の最大値
+    pub peak_viewers: i64,
+    /// スーパーチャットの件数
+    pub superchat_count: u64,
+}
+
+impl SessionCounters {
+    pub fn record_viewers(&mut self, viewers: i64) {
+        self.viewer_sum += viewers;
+        self.viewer_samples += 1;
+        self.peak_viewers = self.peak_viewers.max(viewers);
+    }
+}
+
+/// 配信の集計結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionAnalytics {
+    /// 監視時間（秒）
+    pub duration_seconds: u64,
+    /// 平均同時接続者数
+    pub average_viewers: f64,
+    /// 最大同時接続者数
+    pub peak_viewers: i64,
+    /// スーパーチャット累計金額
+    pub superchat_amount: i64,
+    /// スーパーチャット件数
+    pub superchat_count: u64,
+    /// 項目別のポイント
+    pub points: PointState,
+    /// 新規登録者数
+    pub new_subscribers: i64,
+    /// 1分あたりの平均ポイント
+    pub points_per_minute: f64,
+}
+
+/// ポイント更新時点のポイントと指標（配信後の振り返り用）
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricSnapshot {
+    /// 記録時刻（UNIX ミリ秒）
+    pub timestamp: i64,
+    pub points: PointState,
+    pub metrics: RawMetrics,
+}
+
+/// 履歴の末尾に追加し、`limit` 件を超えた分を古い順に捨てる
+pub fn push_snapshot(
+    history: &mut VecDeque<MetricSnapshot>,
+    snapshot: MetricSnapshot,
+    limit: usize,
+) {
+    history.push_back(snapshot);
+    while history.len() > limit {
+        history.pop_front();
+    }
+}
+
+/// ある時点の指標と合計ポイント（推移のグラフ用）
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricSample {
+    /// 監視開始からの経過秒数
+    pub elapsed_seconds: u64,
+    pub concurrent_viewers: i64,
+    pub like_count: i64,
+    pub superchat_amount: i64,
+    pub new_subscribers: i64,
+    /// 合計ポイント
+    pub total: i64,
+}
+
+impl MetricSample {
+    pub fn new(elapsed_seconds: u64, metrics: &RawMetrics, points: &PointState) -> Self {
+        Self {
+            elapsed_seconds,
+            concurrent_viewers: metrics.concurrent_viewers,
+            like_count: metrics.like_count,
+            superchat_amount: metrics.superchat_amount,
+            new_subscribers: metrics.current_subscribers - metrics.initial_subscribers,
+            total: points.total,
+        }
+    }
+
+    /// 経過時間で等分した最大 `buckets` 個の区間にまとめる
+    ///
+    /// 同時接続者数と高評価数は区間内の平均、それ以外は区間の最後の値を使う。
+    /// サンプルのない区間は出力しない。`samples` は経過時間の昇順であること。
+    pub fn downsample(samples: &[Self], buckets: usize) -> Vec<Self> {
+        let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+            return Vec::new();
+        };
+        if buckets == 0 || samples.len() <= buckets {
+            return samples.to_vec();
+        }
+
+        let start = first.elapsed_seconds;
+        let span = (last.elapsed_seconds - start + 1) as u128;
+        let bucket_of = |sample: &Self| {
+            ((sample.elapsed_seconds - start) as u128 * buckets as u128 / span) as usize
+        };
+
+        samples
+            .chunk_by(|a, b| bucket_of(a) == bucket_of(b))
+            .map(|chunk| {
+                let len = chunk.len() as f64;
+                let average = |value: fn(&Self) -> i64| {
+                    (chunk.iter().map(value).sum::<i64>() as f64 / len).round() as i64
+                };
+                Self {
+                    concurrent_viewers: average(|sample| sample.concurrent_viewers),
+                    like_count: average(|sample| sample.like_count),
+                    ..chunk[chunk.len() - 1].clone()
+                }
+            })
+            .collect()
+    }
+}
+
+/// 目標達成までの見込み
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum GoalEstimate {
+    /// 達成済み
+    Reached,
+    /// 今のペースで `seconds` 秒後に達成
+    Estimated {
+        seconds: u64,
+        remaining: i64,
+        points_per_minute: f64,
+    },
+    /// ペースが 0 以下で達成できない
+    Unreachable {
+        remaining: i64,
+        points_per_minute: f64,
+    },
+}
+
+impl GoalEstimate {
+    /// 監視開始からの平均ペースで目標までの時間を見積もる
+    pub fn compute(goal_points: i64, goal: i64, duration_seconds: u64) -> Self {
+        let remaining = goal - goal_points;
+        if remaining <= 0 {
+            return Self::Reached;
+        }
+        let points_per_minute = if duration_seconds > 0 {
+            goal_points as f64 / (duration_seconds as f64 / 60.0)
+        } else {
+            0.0
+        };
+        if points_per_minute <= 0.0 {
+            return Self::Unreachable {
+                remaining,
+                points_per_minute,
+            };
+        }
+        Self::Estimated {
+            seconds: (remaining as f64 / points_per_minute * 60.0).ceil() as u64,
+            remaining,
+            points_per_minute,
+        }
+    }
+}
+
+/// 段階的な目標の進み具合
+#[derive(Debug, Clone, Serialize)]
+pub struct GoalStatus {
+    /// 現在の目標（すべて達成済みなら最後の目標）
+    pub active: i64,
+    /// 現在の目標の次の目標
+    pub next: Option<i64>,
+    /// 達成済みの目標の数
+    pub reached: usize,
+    /// 目標の総数
+    pub count: usize,
+}
+
+impl GoalStatus {
+    /// 最初の未達成の目標を現在の目標とする
+    pub fn compute(goals: &[i64], value: i64) -> Self {
+        let reached = goals.iter().take_while(|goal| value >= **goal).count();
+        let active_index = reached.min(goals.len().saturating_sub(1));
+        Self {
+            active: goals.get(active_index).copied().unwrap_or_default(),
+            next: goals.get(active_index + 1).copied(),
+            reached,
+            count: goals.len(),
+        }
+    }
+}
+
+impl SessionAnalytics {
+    pub fn compute(
+        points: &PointState,
+        metrics: &RawMetrics,
+        counters: &SessionCounters,
+        duration_seconds: u64,
+    ) -> Self {
+        let average_viewers = if counters.viewer_samples > 0 {
+            counters.viewer_sum as f64 / counters.viewer_samples as f64
+        } else {
+            0.0
+        };
+        let points_per_minute = if duration_seconds > 0 {
+            points.total as f64 / (duration_seconds as f64 / 60.0)
+        } else {
+            0.0
+        };
+
+        Self {
+            duration_seconds,
+            average_viewers,
+            peak_viewers: counters.peak_viewers,
+            superchat_amount: metrics.superchat_amount,
+            superchat_count: counters.superchat_count,
+            points: points.clone(),
+            new_subscribers: metrics.current_subscribers - metrics.initial_subscribers,
+            points_per_minute,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn currency_config() -> CurrencyConfig {
+        CurrencyConfig {
+            base: "JPY".to_string(),
+            rates: BTreeMap::from([("USD".to_string(), 150.0)]),
+            fallback_rate: 100.0,
+        }
+    }
+
+    #[test]
+    fn base_currency_amount_is_unchanged() {
+        assert_eq!(normalize_amount(500, "JPY", &currency_config()), 500);
+        assert_eq!(normalize_amount(500, "jpy", &currency_config()), 500);
+    }
+
+    #[test]
+    fn known_currency_is_converted() {
+        assert_eq!(normalize_amount(5, "USD", &currency_config()), 750);
+    }
+
+    #[test]
+    fn unknown_currency_uses_fallback_rate() {
+        assert_eq!(normalize_amount(3, "XYZ", &currency_config()), 300);
+    }
+
+    fn points_config() -> PointsConfig {
+        PointsConfig {
+            superchat_rate: 10.0,
+            like_rate: 1.0,
+            subscriber_rate: 1.0,
+            manual_rate: 100.0,
+            visitor_rate: 200.0,
+            membership_rate: 50.0,
+            total_cap: None,
+            superchat_cap: None,
+            concurrent_cap: None,
+            like_cap: None,
+            subscriber_cap: None,
+            milestones: Vec::new(),
+            ..PointsConfig::default()
+        }
+    }
+
+    fn sample_metrics() -> RawMetrics {
+        RawMetrics {
+            superchat_amount: 1000,
+            concurrent_viewers: 80,
+            like_count: 30,
+            membership_count: 2,
+            ..Default::default()
+        }
+    }
+
+    fn sample_inputs() -> PointInputs {
+        PointInputs {
+            subscribers: 5,
+            manual: 2,
+            visitor: 1,
+            concurrent_bonus_given: true,
+            milestone_bonus: 50,
+        }
+    }
+
+    #[test]
+    fn total_adds_every_source() {
+        let points = PointState::calculate(&sample_metrics(), &sample_inputs(), &points_config());
+
+        assert_eq!(points.superchat, 100);
+        assert_eq!(points.concurrent, CONCURRENT_BONUS_POINTS);
+        assert_eq!(points.likes, 30);
+        assert_eq!(points.membership, 100);
+        assert_eq!(points.bonus, 50);
+        // Hand-entered counts are kept as counts and converted only in the total
+        assert_eq!(
+            (points.subscribers, points.manual, points.visitor),
+            (5, 2, 1)
+        );
+        assert_eq!(
+            points.total,
+            100 + CONCURRENT_BONUS_POINTS + 30 + 5 + 100 + 50 + 2 * 100 + 200
+        );
+    }
+
+    #[test]
+    fn concurrent_points_need_the_bonus() {
+        let inputs = PointInputs {
+            concurrent_bonus_given: false,
+            ..sample_inputs()
+        };
+        let points = PointState::calculate(&sample_metrics(), &inputs, &points_config());
+        assert_eq!(points.concurrent, 0);
+    }
+
+    #[test]
+    fn each_source_is_clamped_to_its_cap() {
+        let config = PointsConfig {
+            superchat_cap: Some(40),
+            concurrent_cap: Some(300),
+            like_cap: Some(10),
+            subscriber_cap: Some(3),
+            ..points_config()
+        };
+        let points = PointState::calculate(&sample_metrics(), &sample_inputs(), &config);
+
+        assert_eq!(points.superchat, 40);
+        assert_eq!(points.concurrent, 300);
+        assert_eq!(points.likes, 10);
+        // Uncapped sources are unaffected
+        assert_eq!(points.membership, 100);
+        assert_eq!(points.total, 40 + 300 + 10 + 3 + 100 + 50 + 2 * 100 + 200);
+    }
+
+    #[test]
+    fn cap_above_points_changes_nothing() {
+        let config = PointsConfig {
+            superchat_cap: Some(1_000_000),
+            like_cap: Some(1_000_000),
+            ..points_config()
+        };
+        let capped = PointState::calculate(&sample_metrics(), &sample_inputs(), &config);
+        let uncapped = PointState::calculate(&sample_metrics(), &sample_inputs(), &points_config());
+        assert_eq!(capped.total, uncapped.total);
+    }
+
+    #[test]
+    fn disabled_sources_add_nothing() {
+        let config = PointsConfig {
+            superchat_enabled: false,
+            concurrent_enabled: false,
+            like_enabled: false,
+            subscriber_enabled: false,
+            ..points_config()
+        };
+        let metrics = sample_metrics();
+        let points = PointState::calculate(&metrics, &sample_inputs(), &config);
+
+        assert_eq!(
+            (points.superchat, points.concurrent, points.likes),
+            (0, 0, 0)
+        );
+        // Hand-entered subscribers are still shown but not counted
+        assert_eq!(points.subscribers, 5);
+        assert_eq!(points.total, 100 + 50 + 2 * 100 + 200);
+        // The metrics themselves are still tracked
+        assert_eq!(metrics.superchat_amount, 1000);
+        assert_eq!(metrics.like_count, 30);
+        assert_eq!(metrics.concurrent_viewers, 80);
+    }
+
+    #[test]
+    fn disabling_one_source_removes_only_its_points() {
+        let enabled = PointState::calculate(&sample_metrics(), &sample_inputs(), &points_config());
+        let config = PointsConfig {
+            like_enabled: false,
+            ..points_config()
+        };
+        let disabled = PointState::calculate(&sample_metrics(), &sample_inputs(), &config);
+
+        assert_eq!(disabled.likes, 0);
+        assert_eq!(disabled.total, enabled.total - enabled.likes);
+    }
+
+    #[test]
+    fn visitor_points_survive_recalculation() {
+        let stored = PointState {
+            visitor: 3,
+            ..Default::default()
+        };
+        // The next poll brings new metrics; the visitor count comes from the stored points
+        let metrics = RawMetrics {
+            like_count: 30,
+            ..Default::default()
+        };
+        let points = PointState::calculate(
+            &metrics,
+            &PointInputs::from_counts(&stored),
+            &points_config(),
+        );
+
+        assert_eq!(points.visitor, 3);
+        assert_eq!(points.total, points.likes + 3 * 200);
+    }
+
+    fn feed_viewers(readings: &[i64]) -> Vec<i64> {
+        let mut metrics = RawMetrics::default();
+        let mut dip_pending = false;
+        readings
+            .iter()
+            .map(|&viewers| {
+                metrics.update_viewers(viewers, true, &mut dip_pending);
+                metrics.concurrent_viewers
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_viewer_dip_is_held_back() {
+        let n = VIEWER_DIP_MIN_PREVIOUS * 10;
+        assert_eq!(feed_viewers(&[n, 0, n]), [n, n, n]);
+    }
+
+    #[test]
+    fn repeated_low_reading_is_accepted() {
+        let n = VIEWER_DIP_MIN_PREVIOUS * 10;
+        assert_eq!(feed_viewers(&[n, 0, 0]), [n, n, 0]);
+    }
+
+    #[test]
+    fn viewer_dips_are_accepted_when_not_suppressed() {
+        let n = VIEWER_DIP_MIN_PREVIOUS * 10;
+        let mut metrics = RawMetrics::default();
+        let mut dip_pending = false;
+        assert!(metrics.update_viewers(n, false, &mut dip_pending));
+        assert!(metrics.update_viewers(0, false, &mut dip_pending));
+        assert_eq!(metrics.concurrent_viewers, 0);
+        assert!(!dip_pending);
+    }
+
+    #[test]
+    fn falling_like_count_is_clamped_when_enabled() {
+        let config = points_config();
+        let mut metrics = RawMetrics::default();
+        metrics.update_likes(Some(120), true);
+        metrics.update_likes(Some(90), true);
+        // The raw count follows the stream; only the points keep the high
+        assert_eq!(metrics.like_count, 90);
+        assert_eq!(metrics.like_high, 120);
+        let points = PointState::calculate(&metrics, &PointInputs::default(), &config);
+        assert_eq!(points.likes, (120.0 / config.like_rate) as i64);
+
+        metrics.update_likes(Some(150), true);
+        assert_eq!(metrics.like_count, 150);
+        assert_eq!(metrics.like_high, 150);
+    }
+
+    #[test]
+    fn clamp_keeps_the_starting_like_count() {
+        // Metrics built at startup only set the raw count
+        let mut metrics = RawMetrics {
+            like_count: 100,
+            ..Default::default()
+        };
+        metrics.update_likes(Some(80), true);
+        assert_eq!(metrics.like_count, 80);
+        assert_eq!(metrics.points_like_count(), 100);
+    }
+
+    #[test]
+    fn falling_like_count_is_followed_when_disabled() {
+        let config = points_config();
+        let mut metrics = RawMetrics::default();
+        metrics.update_likes(Some(120), false);
+        metrics.update_likes(Some(90), false);
+        assert_eq!(metrics.like_count, 90);
+        let points = PointState::calculate(&metrics, &PointInputs::default(), &config);
+        assert_eq!(points.likes, (90.0 / config.like_rate) as i64);
+    }
+
+    #[test]
+    fn hidden_like_count_keeps_previous_value() {
+        for clamp_negative in [false, true] {
+            let mut metrics = RawMetrics::default();
+            metrics.update_likes(Some(120), clamp_negative);
+            metrics.update_likes(None, clamp_negative);
+            assert_eq!(metrics.like_count, 120);
+            assert_eq!(metrics.points_like_count(), 120);
+        }
+    }
+
+    fn snapshot(timestamp: i64) -> MetricSnapshot {
+        MetricSnapshot {
+            timestamp,
+            points: PointState::default(),
+            metrics: RawMetrics::default(),
+        }
+    }
+
+    #[test]
+    fn history_drops_oldest_snapshots_past_limit() {
+        let mut history = VecDeque::new();
+        for timestamp in 0..3 {
+            push_snapshot(&mut history, snapshot(timestamp), 3);
+        }
+        assert_eq!(history.len(), 3);
+
+        push_snapshot(&mut history, snapshot(3), 3);
+        push_snapshot(&mut history, snapshot(4), 3);
+        let timestamps: Vec<i64> = history.iter().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, [2, 3, 4]);
+    }
+
+    fn breakdown(total: i64) -> PointState {
+        PointState {
+            total,
+            superchat: 60,
+            concurrent: 30,
+            likes: 20,
+            manual: 5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn total_below_cap_is_left_alone() {
+        let mut points = breakdown(99);
+        assert!(!points.apply_cap(Some(100)));
+        assert_eq!(points.total, 99);
+        assert!(!points.apply_cap(None));
+        assert_eq!(points.total, 99);
+    }
+
+    #[test]
+    fn total_at_or_above_cap_is_capped() {
+        let mut points = breakdown(100);
+        assert!(points.apply_cap(Some(100)));
+        assert_eq!(points.total, 100);
+
+        let mut points = breakdown(115);
+        assert!(points.apply_cap(Some(100)));
+        assert_eq!(points.total, 100);
+    }
+
+    #[test]
+    fn capping_keeps_breakdown() {
+        let mut points = breakdown(115);
+        points.apply_cap(Some(100));
+        assert_eq!(
+            (
+                points.superchat,
+                points.concurrent,
+                points.likes,
+                points.manual
+            ),
+            (60, 30, 20, 5)
+        );
     }
 }
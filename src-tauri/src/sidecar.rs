@@ -19,7 +19,84 @@ struct RpcRequest {
 struct RpcResponse {
     id: u64,
     result: Option<serde_json::Value>,
-    error: Option<String>,
+    error: Option<RpcError>,
+}
+
+/// レスポンスの `error`（`{"code", "message"}` または旧形式の文字列）
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RpcError {
+    Structured {
+        code: SidecarErrorCode,
+        message: String,
+    },
+    Legacy(String),
+}
+
+/// サイドカー呼び出しのエラー種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SidecarErrorCode {
+    /// パラメータが不正
+    InvalidParams,
+    /// 未知のメソッド
+    MethodNotFound,
+    /// `init` 前に呼び出された
+    NotInitialized,
+    /// 認証が必要
+    Unauthenticated,
+    /// 動画・チャンネル・チャットが見つからない
+    NotFound,
+    /// YouTube 側のレート制限
+    RateLimited,
+    /// サイドカー内部のエラー
+    Internal,
+    /// 応答がタイムアウトした
+    Timeout,
+    /// サイドカーが起動していない、または通信できない
+    NotRunning,
+    /// 応答の形式が不正
+    InvalidResponse,
+    /// 旧形式の文字列エラーや未知のコード
+    #[serde(other)]
+    Unknown,
+}
+
+/// サイドカー呼び出しのエラー
+#[derive(Debug, Clone)]
+pub struct SidecarError {
+    pub code: SidecarErrorCode,
+    pub message: String,
+}
+
+impl SidecarError {
+    fn new(code: SidecarErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SidecarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<SidecarError> for String {
+    fn from(error: SidecarError) -> Self {
+        error.message
+    }
+}
+
+impl From<RpcError> for SidecarError {
+    fn from(error: RpcError) -> Self {
+        match error {
+            RpcError::Structured { code, message } => Self::new(code, message),
+            RpcError::Legacy(message) => Self::new(SidecarErrorCode::Unknown, message),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +109,28 @@ pub struct SuperchatEventData {
     pub timestamp: i64,
 }
 
+/// メンバーシップイベントの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MembershipKind {
+    /// 新規加入
+    New,
+    /// 継続（マイルストーン）
+    Milestone,
+    /// メンバーシップギフト
+    Gift,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MembershipEventData {
+    pub id: String,
+    pub author: String,
+    pub kind: MembershipKind,
+    /// 加入したメンバー数（ギフトでは贈られた数、それ以外は 1）
+    pub count: i64,
+    pub timestamp: i64,
+}
+
 #[derive(Debug, Deserialize)]
 struct PushEvent {
     event: EventPayload,
@@ -42,105 +141,322 @@ struct PushEvent {
 enum EventPayload {
     #[serde(rename = "superchat")]
     Superchat(SuperchatEventData),
+    #[serde(rename = "membership")]
+    Membership(MembershipEventData),
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// `getLiveInfo` の結果
+///
+/// `channelId` と `isLive` 以外は欠けていたり `null` だったりしても受け付ける。
+/// 配信開始直後などに一部の値が取れなくても、取れた値で更新を続けるため。
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LiveInfo {
-    #[serde(rename = "videoId")]
+    #[serde(rename = "videoId", default, deserialize_with = "null_as_default")]
     pub video_id: String,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub title: String,
     #[serde(rename = "channelId")]
     pub channel_id: String,
-    #[serde(rename = "channelName")]
+    #[serde(rename = "channelName", default, deserialize_with = "null_as_default")]
     pub channel_name: String,
-    #[serde(rename = "concurrentViewers")]
-    pub concurrent_viewers: i64,
+    #[serde(rename = "concurrentViewers", default)]
+    pub concurrent_viewers: Option<i64>,
     #[serde(rename = "likeCount", default)]
     pub like_count: Option<i64>,
     #[serde(rename = "isLive")]
     pub is_live: bool,
+    /// 表示専用の追加指標（例: 総視聴時間）
+    #[serde(rename = "extraMetrics", default)]
+    pub extra_metrics: Option<HashMap<String, i64>>,
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// これを超えて閉じない JSON が溜まったら出力を破棄する（バイト）
+const MAX_BUFFERED_OUTPUT: usize = 1024 * 1024;
+
+/// サイドカーの stdout を蓄積し、完結した JSON 値を順に取り出す
+///
+/// 1 行 1 JSON（NDJSON）が前提だが、複数行に整形された JSON も値が完結した時点で
+/// 受け付ける。JSON として解釈できない行は警告を出して読み飛ばす。
+#[derive(Default)]
+struct JsonStreamBuffer {
+    buffer: String,
+}
+
+impl JsonStreamBuffer {
+    fn push(&mut self, chunk: &str) -> Vec<serde_json::Value> {
+        self.buffer.push_str(chunk);
+        // Lines may arrive with the newline stripped; it is needed to skip bad lines
+        if !chunk.ends_with('\n') {
+            self.buffer.push('\n');
+        }
+
+        let mut values = Vec::new();
+        loop {
+            let mut stream =
+                serde_json::Deserializer::from_str(&self.buffer).into_iter::<serde_json::Value>();
+            match stream.next() {
+                Some(Ok(value)) => {
+                    let consumed = stream.byte_offset();
+                    values.push(value);
+                    self.buffer.drain(..consumed);
+                }
+                // Incomplete value: wait for the rest of it
+                Some(Err(e)) if e.is_eof() => {
+                    if self.buffer.len() > MAX_BUFFERED_OUTPUT {
+                        eprintln!("[sidecar] Discarding unterminated JSON output");
+                        self.buffer.clear();
+                    }
+                    break;
+                }
+                Some(Err(e)) => {
+                    let end = self.buffer.find('\n').map_or(self.buffer.len(), |i| i + 1);
+                    eprintln!(
+                        "[sidecar] Skipping non-JSON output ({}): {}",
+                        e,
+                        self.buffer[..end].trim_end()
+                    );
+                    self.buffer.drain(..end);
+                }
+                None => {
+                    self.buffer.clear();
+                    break;
+                }
+            }
+        }
+        values
+    }
+}
+
+/// サイドカーとのやり取りの経路（本番はシェルで起動したプロセス）
+///
+/// プロセスを使わずに RPC の処理を動かせるよう、書き込みと終了だけを抽象化する。
+/// 出力は [`TransportEvent`] として [`SidecarManager::attach`] に渡す。
+pub trait SidecarTransport: Send {
+    /// stdin に書き込む
+    fn write(&mut self, data: &[u8]) -> Result<(), String>;
+    /// 終了させる
+    fn kill(self: Box<Self>) -> Result<(), String>;
+}
+
+impl SidecarTransport for CommandChild {
+    fn write(&mut self, data: &[u8]) -> Result<(), String> {
+        CommandChild::write(self, data).map_err(|e| e.to_string())
+    }
+
+    fn kill(self: Box<Self>) -> Result<(), String> {
+        CommandChild::kill(*self).map_err(|e| e.to_string())
+    }
+}
+
+/// サイドカーからの出力
+#[derive(Debug)]
+pub enum TransportEvent {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+    /// 終了した（内容は終了理由）
+    Terminated(String),
+}
+
+/// サイドカーの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SidecarStatus {
+    /// プロセスが動いていない
+    Stopped,
+    /// プロセスが動いていて、直近の `ping` に応答した
+    Running,
+    /// プロセスは動いているが、直近の `ping` に応答しなかった
+    Unresponsive,
 }
 
-type ResponseSender = oneshot::Sender<Result<serde_json::Value, String>>;
+/// 終了時に `stopLiveChat` の応答を待つ最大時間（過ぎたら強制終了する）
+const GRACEFUL_STOP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+type ResponseSender = oneshot::Sender<Result<serde_json::Value, SidecarError>>;
 type PendingRequests = Arc<Mutex<HashMap<u64, ResponseSender>>>;
 
+/// サイドカープロセスとの JSON-RPC 通信を管理する
+///
+/// 代替サイドカーが満たすべき仕様:
+/// - stdin で 1 行 1 JSON のリクエスト `{"id", "method", "params"}` を受け取る
+/// - stdout に 1 行 1 JSON でレスポンス `{"id", "result"}` または `{"id", "error"}` を返す
+///   （複数行に整形された JSON も受け付けるが、JSON 以外の行は読み飛ばされる）
+///   （`error` は `{"code", "message"}`。`code` は [`SidecarErrorCode`] の snake_case 名。
+///   文字列だけの `error` も受け付ける）
+/// - プッシュイベントは `{"event": {"type": "superchat", "data": {...}}}` 形式で送る
+///   （`type` は `superchat` か `membership`）
+/// - `getLiveInfo` の結果には表示専用の `extraMetrics`（名前と整数値の組）を含めてよい
+/// - メソッド: `init` (`{"authenticated"}` を返す), `setCookies`, `getLiveInfo`,
+///   `getSubscriberCount`, `getExactSubscriberCount` (`{"count"}` を返す),
+///   `startLiveChat`, `stopLiveChat`, `ping`
 pub struct SidecarManager {
-    child: Arc<Mutex<Option<CommandChild>>>,
+    name: String,
+    child: Arc<Mutex<Option<Box<dyn SidecarTransport>>>>,
     request_id: AtomicU64,
     pending: PendingRequests,
-    superchat_tx: Option<mpsc::UnboundedSender<SuperchatEventData>>,
+    /// 起動後にも差し替えられるよう共有する（事前起動したサイドカーを監視で使うため）
+    superchat_tx: SuperchatHandler,
+    membership_tx: MembershipHandler,
+    /// 予期せず終了したときに終了理由を送る先
+    termination_tx: TerminationHandler,
+    /// RPC の応答を待つ最大時間
+    rpc_timeout: std::time::Duration,
 }
 
+type SuperchatHandler = Arc<Mutex<Option<mpsc::UnboundedSender<SuperchatEventData>>>>;
+type MembershipHandler = Arc<Mutex<Option<mpsc::UnboundedSender<MembershipEventData>>>>;
+type TerminationHandler = Arc<Mutex<Option<mpsc::UnboundedSender<String>>>>;
+
 impl SidecarManager {
     pub fn new() -> Self {
         Self {
+            name: crate::config::DEFAULT_SIDECAR_NAME.to_string(),
             child: Arc::new(Mutex::new(None)),
             request_id: AtomicU64::new(0),
             pending: Arc::new(Mutex::new(HashMap::new())),
-            superchat_tx: None,
+            superchat_tx: Arc::new(Mutex::new(None)),
+            membership_tx: Arc::new(Mutex::new(None)),
+            termination_tx: Arc::new(Mutex::new(None)),
+            rpc_timeout: std::time::Duration::from_secs(
+                crate::config::SidecarConfig::default().rpc_timeout_seconds,
+            ),
         }
     }
 
-    pub fn set_superchat_handler(&mut self, tx: mpsc::UnboundedSender<SuperchatEventData>) {
-        self.superchat_tx = Some(tx);
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn set_rpc_timeout(&mut self, timeout: std::time::Duration) {
+        self.rpc_timeout = timeout;
+    }
+
+    pub async fn set_superchat_handler(&self, tx: mpsc::UnboundedSender<SuperchatEventData>) {
+        let mut superchat_tx = self.superchat_tx.lock().await;
+        *superchat_tx = Some(tx);
+    }
+
+    pub async fn set_membership_handler(&self, tx: mpsc::UnboundedSender<MembershipEventData>) {
+        let mut membership_tx = self.membership_tx.lock().await;
+        *membership_tx = Some(tx);
+    }
+
+    /// Receives the reason when the process exits without `stop`/`kill` being called
+    pub async fn set_termination_handler(&self, tx: mpsc::UnboundedSender<String>) {
+        let mut termination_tx = self.termination_tx.lock().await;
+        *termination_tx = Some(tx);
+    }
+
+    /// Whether the process is still running (it is forgotten once it terminates)
+    pub async fn is_running(&self) -> bool {
+        self.child.lock().await.is_some()
     }
 
     pub async fn start(&mut self, app: &tauri::AppHandle) -> Result<(), String> {
         let sidecar = app
             .shell()
-            .sidecar("youtube-sidecar")
+            .sidecar(&self.name)
             .map_err(|e| format!("Failed to create sidecar command: {}", e))?;
 
         let (mut rx, child) = sidecar
             .spawn()
             .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
 
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        tauri::async_runtime::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let event = match event {
+                    CommandEvent::Stdout(line) => TransportEvent::Stdout(line),
+                    CommandEvent::Stderr(line) => TransportEvent::Stderr(line),
+                    CommandEvent::Terminated(payload) => {
+                        TransportEvent::Terminated(format!("{:?}", payload))
+                    }
+                    _ => continue,
+                };
+                if events_tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.attach(Box::new(child), events_rx).await;
+        Ok(())
+    }
+
+    /// Starts speaking JSON-RPC over `transport`, reading its output from `events`
+    pub async fn attach(
+        &mut self,
+        transport: Box<dyn SidecarTransport>,
+        mut events: mpsc::UnboundedReceiver<TransportEvent>,
+    ) {
         {
             let mut child_guard = self.child.lock().await;
-            *child_guard = Some(child);
+            *child_guard = Some(transport);
         }
 
         let pending = self.pending.clone();
         let superchat_tx = self.superchat_tx.clone();
+        let membership_tx = self.membership_tx.clone();
+        let termination_tx = self.termination_tx.clone();
+        let child = self.child.clone();
 
         tauri::async_runtime::spawn(async move {
-            while let Some(event) = rx.recv().await {
+            let mut stdout = JsonStreamBuffer::default();
+            let mut termination = "Output stream closed".to_string();
+            while let Some(event) = events.recv().await {
                 match event {
-                    CommandEvent::Stdout(line) => {
-                        let text = String::from_utf8_lossy(&line);
-                        for line in text.lines() {
-                            if line.is_empty() {
-                                continue;
-                            }
-                            Self::handle_stdout_line(line, &pending, &superchat_tx).await;
+                    TransportEvent::Stdout(line) => {
+                        for value in stdout.push(&String::from_utf8_lossy(&line)) {
+                            Self::handle_message(value, &pending, &superchat_tx, &membership_tx)
+                                .await;
                         }
                     }
-                    CommandEvent::Stderr(line) => {
+                    TransportEvent::Stderr(line) => {
                         let text = String::from_utf8_lossy(&line);
                         eprintln!("[sidecar] {}", text);
                     }
-                    CommandEvent::Terminated(payload) => {
-                        eprintln!("[sidecar] Terminated: {:?}", payload);
+                    TransportEvent::Terminated(reason) => {
+                        eprintln!("[sidecar] Terminated: {}", reason);
+                        termination = reason;
                         break;
                     }
-                    _ => {}
                 }
             }
+            // Later calls fail fast with `NotRunning`, and waiting calls are answered now
+            // instead of timing out. `stop`/`kill` take the child first, so finding it still
+            // here means the process exited on its own.
+            let unexpected = child.lock().await.take().is_some();
+            pending.lock().await.clear();
+            if unexpected && let Some(tx) = termination_tx.lock().await.as_ref() {
+                let _ = tx.send(termination);
+            }
         });
-
-        Ok(())
     }
 
-    async fn handle_stdout_line(
-        line: &str,
+    async fn handle_message(
+        message: serde_json::Value,
         pending: &PendingRequests,
-        superchat_tx: &Option<mpsc::UnboundedSender<SuperchatEventData>>,
+        superchat_tx: &SuperchatHandler,
+        membership_tx: &MembershipHandler,
     ) {
         // Try parsing as push event first
-        if let Ok(push) = serde_json::from_str::<PushEvent>(line) {
+        if let Ok(push) = PushEvent::deserialize(&message) {
             match push.event {
                 EventPayload::Superchat(data) => {
-                    if let Some(tx) = superchat_tx {
+                    if let Some(tx) = superchat_tx.lock().await.as_ref() {
+                        let _ = tx.send(data);
+                    }
+                }
+                EventPayload::Membership(data) => {
+                    if let Some(tx) = membership_tx.lock().await.as_ref() {
                         let _ = tx.send(data);
                     }
                 }
@@ -149,11 +465,11 @@ impl SidecarManager {
         }
 
         // Try parsing as RPC response
-        if let Ok(response) = serde_json::from_str::<RpcResponse>(line) {
+        if let Ok(response) = RpcResponse::deserialize(&message) {
             let mut pending = pending.lock().await;
             if let Some(sender) = pending.remove(&response.id) {
                 let result = if let Some(error) = response.error {
-                    Err(error)
+                    Err(error.into())
                 } else {
                     Ok(response.result.unwrap_or(serde_json::Value::Null))
                 };
@@ -166,7 +482,17 @@ impl SidecarManager {
         &self,
         method: &str,
         params: Option<serde_json::Value>,
-    ) -> Result<serde_json::Value, String> {
+    ) -> Result<serde_json::Value, SidecarError> {
+        self.call_with_timeout(method, params, self.rpc_timeout)
+            .await
+    }
+
+    async fn call_with_timeout(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+        timeout: std::time::Duration,
+    ) -> Result<serde_json::Value, SidecarError> {
         let id = self.request_id.fetch_add(1, Ordering::SeqCst) + 1;
         let request = RpcRequest {
             id,
@@ -174,7 +500,8 @@ impl SidecarManager {
             params,
         };
 
-        let json = serde_json::to_string(&request).map_err(|e| e.to_string())?;
+        let json = serde_json::to_string(&request)
+            .map_err(|e| SidecarError::new(SidecarErrorCode::InvalidParams, e.to_string()))?;
 
         let (tx, rx) = oneshot::channel();
         {
@@ -184,32 +511,48 @@ impl SidecarManager {
 
         {
             let mut child_guard = self.child.lock().await;
-            let child = child_guard
-                .as_mut()
-                .ok_or_else(|| "Sidecar not running".to_string())?;
-            child
-                .write((json + "\n").as_bytes())
-                .map_err(|e| format!("Failed to write to sidecar: {}", e))?;
+            let child = child_guard.as_mut().ok_or_else(|| {
+                SidecarError::new(SidecarErrorCode::NotRunning, "Sidecar not running")
+            })?;
+            child.write((json + "\n").as_bytes()).map_err(|e| {
+                SidecarError::new(
+                    SidecarErrorCode::NotRunning,
+                    format!("Failed to write to sidecar: {}", e),
+                )
+            })?;
         }
 
-        match tokio::time::timeout(std::time::Duration::from_secs(30), rx).await {
+        match tokio::time::timeout(timeout, rx).await {
             Ok(Ok(result)) => result,
-            Ok(Err(_)) => Err("Response channel closed".to_string()),
+            Ok(Err(_)) => Err(SidecarError::new(
+                SidecarErrorCode::NotRunning,
+                "Response channel closed",
+            )),
             Err(_) => {
                 let mut pending = self.pending.lock().await;
                 pending.remove(&id);
-                Err("Request timeout".to_string())
+                Err(SidecarError::new(
+                    SidecarErrorCode::Timeout,
+                    "Request timeout",
+                ))
             }
         }
     }
 
-    pub async fn init(&self) -> Result<bool, String> {
+    /// Checks that the process still answers, without touching YouTube
+    pub async fn ping(&self) -> Result<(), String> {
+        let timeout = std::time::Duration::from_secs(crate::config::SIDECAR_PING_TIMEOUT_SECONDS);
+        self.call_with_timeout("ping", None, timeout).await?;
+        Ok(())
+    }
+
+    pub async fn init(&self) -> Result<bool, SidecarError> {
         let result = self.call("init", None).await?;
         let authenticated = result["authenticated"].as_bool().unwrap_or(false);
         Ok(authenticated)
     }
 
-    pub async fn set_cookies(&self, cookies: &str) -> Result<(), String> {
+    pub async fn set_cookies(&self, cookies: &str) -> Result<(), SidecarError> {
         self.call(
             "setCookies",
             Some(serde_json::json!({ "cookies": cookies })),
@@ -218,62 +561,84 @@ impl SidecarManager {
         Ok(())
     }
 
-    pub async fn get_live_info(&self, video_id: &str) -> Result<LiveInfo, String> {
+    /// 保存済みの Cookie を破棄する（次の `init` から未認証になる）
+    pub async fn clear_cookies(&self) -> Result<(), SidecarError> {
+        self.call("clearCookies", None).await?;
+        Ok(())
+    }
+
+    pub async fn get_live_info(&self, video_id: &str) -> Result<LiveInfo, SidecarError> {
         let result = self
             .call(
                 "getLiveInfo",
                 Some(serde_json::json!({ "videoId": video_id })),
             )
             .await?;
-        serde_json::from_value(result).map_err(|e| e.to_string())
+        serde_json::from_value(result)
+            .map_err(|e| SidecarError::new(SidecarErrorCode::InvalidResponse, e.to_string()))
     }
 
-    pub async fn get_subscriber_count(&self, channel_id: &str) -> Result<i64, String> {
+    pub async fn get_subscriber_count(&self, channel_id: &str) -> Result<i64, SidecarError> {
         let result = self
             .call(
                 "getSubscriberCount",
                 Some(serde_json::json!({ "channelId": channel_id })),
             )
             .await?;
-        result["count"]
-            .as_i64()
-            .ok_or_else(|| "Invalid subscriber count".to_string())
+        result["count"].as_i64().ok_or_else(|| {
+            SidecarError::new(
+                SidecarErrorCode::InvalidResponse,
+                "Invalid subscriber count",
+            )
+        })
     }
 
-    pub async fn get_exact_subscriber_count(&self) -> Result<i64, String> {
+    pub async fn get_exact_subscriber_count(&self) -> Result<i64, SidecarError> {
         let result = self.call("getExactSubscriberCount", None).await?;
-        result["count"]
-            .as_i64()
-            .ok_or_else(|| "Invalid subscriber count".to_string())
+        result["count"].as_i64().ok_or_else(|| {
+            SidecarError::new(
+                SidecarErrorCode::InvalidResponse,
+                "Invalid subscriber count",
+            )
+        })
     }
 
-    pub async fn start_live_chat(&self, video_id: &str) -> Result<(), String> {
+    /// Starts live chat monitoring. `chat_id` overrides the video ID used to resolve the chat.
+    pub async fn start_live_chat(
+        &self,
+        video_id: &str,
+        chat_id: Option<&str>,
+    ) -> Result<(), SidecarError> {
         self.call(
             "startLiveChat",
-            Some(serde_json::json!({ "videoId": video_id })),
+            Some(serde_json::json!({ "videoId": video_id, "chatId": chat_id })),
         )
         .await?;
         Ok(())
     }
 
-    pub async fn stop_live_chat(&self) -> Result<(), String> {
+    pub async fn stop_live_chat(&self) -> Result<(), SidecarError> {
         self.call("stopLiveChat", None).await?;
         Ok(())
     }
 
     pub async fn stop(&mut self) -> Result<(), String> {
-        // Stop live chat first
+        // Stop live chat first, without letting a hung sidecar block the shutdown
+        if self.is_running().await
+            && tokio::time::timeout(GRACEFUL_STOP_TIMEOUT, self.stop_live_chat())
+                .await
+                .is_err()
         {
-            let child_guard = self.child.lock().await;
-            if child_guard.is_some() {
-                drop(child_guard);
-                let _ = self.stop_live_chat().await;
-            }
+            eprintln!("Sidecar did not stop live chat in time, killing it");
         }
+        self.kill().await
+    }
 
+    /// Kills the process right away, skipping `stopLiveChat`
+    pub async fn kill(&self) -> Result<(), String> {
         let mut child_guard = self.child.lock().await;
         if let Some(child) = child_guard.take() {
-            child.kill().map_err(|e| e.to_string())?;
+            child.kill()?;
         }
         Ok(())
     }
@@ -314,12 +679,21 @@ pub fn extract_video_id(url_or_id: &str) -> Result<String, String> {
     if let Ok(url) = url::Url::parse(url_or_id) {
         // youtube.com/watch?v=VIDEO_ID
         if let Some(host) = url.host_str() {
+            // (also m.youtube.com; the parsed host is already lowercase)
             if host.contains("youtube.com") {
                 for (key, value) in url.query_pairs() {
                     if key == "v" {
                         return Ok(value.to_string());
                     }
                 }
+                // youtube.com/live/VIDEO_ID and youtube.com/shorts/VIDEO_ID
+                if let Some(mut path) = url.path_segments()
+                    && matches!(path.next(), Some("live" | "shorts"))
+                    && let Some(id) = path.next()
+                    && !id.is_empty()
+                {
+                    return Ok(id.to_string());
+                }
             }
             // youtu.be/VIDEO_ID
             if host == "youtu.be"
@@ -334,3 +708,325 @@ pub fn extract_video_id(url_or_id: &str) -> Result<String, String> {
 
     Err("Invalid YouTube URL or video ID".to_string())
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// In-memory transport: written bytes are collected, output is fed through a channel
+    struct MemoryTransport {
+        written: Arc<std::sync::Mutex<Vec<u8>>>,
+        kill_error: Option<String>,
+    }
+
+    impl SidecarTransport for MemoryTransport {
+        fn write(&mut self, data: &[u8]) -> Result<(), String> {
+            self.written.lock().unwrap().extend_from_slice(data);
+            Ok(())
+        }
+
+        fn kill(self: Box<Self>) -> Result<(), String> {
+            match self.kill_error {
+                Some(error) => Err(error),
+                None => Ok(()),
+            }
+        }
+    }
+
+    /// A `SidecarManager` attached to a [`MemoryTransport`], with the test playing the sidecar
+    pub(crate) struct Harness {
+        pub(crate) manager: SidecarManager,
+        written: Arc<std::sync::Mutex<Vec<u8>>>,
+        events: mpsc::UnboundedSender<TransportEvent>,
+    }
+
+    impl Harness {
+        pub(crate) async fn new() -> Self {
+            Self::with_kill_error(None).await
+        }
+
+        /// Like `new`, but killing the sidecar fails with `error`
+        pub(crate) async fn failing_kill(error: &str) -> Self {
+            Self::with_kill_error(Some(error.to_string())).await
+        }
+
+        async fn with_kill_error(kill_error: Option<String>) -> Self {
+            let written = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let (events, events_rx) = mpsc::unbounded_channel();
+            let mut manager = SidecarManager::new();
+            let transport = MemoryTransport {
+                written: written.clone(),
+                kill_error,
+            };
+            manager.attach(Box::new(transport), events_rx).await;
+            Self {
+                manager,
+                written,
+                events,
+            }
+        }
+
+        /// Requests written so far, oldest first
+        pub(crate) fn requests(&self) -> Vec<serde_json::Value> {
+            String::from_utf8(self.written.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+
+        /// Waits until at least `count` requests have been written
+        pub(crate) async fn wait_for_requests(&self, count: usize) -> Vec<serde_json::Value> {
+            loop {
+                let requests = self.requests();
+                if requests.len() >= count {
+                    return requests;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }
+
+        pub(crate) fn respond(&self, id: u64, result: serde_json::Value) {
+            self.send_line(serde_json::json!({ "id": id, "result": result }));
+        }
+
+        pub(crate) fn respond_error(&self, id: u64, message: &str) {
+            self.send_line(serde_json::json!({ "id": id, "error": message }));
+        }
+
+        pub(crate) fn terminate(&self, reason: &str) {
+            let _ = self
+                .events
+                .send(TransportEvent::Terminated(reason.to_string()));
+        }
+
+        fn send_line(&self, value: serde_json::Value) {
+            let line = value.to_string().into_bytes();
+            let _ = self.events.send(TransportEvent::Stdout(line));
+        }
+    }
+
+    fn request_id(request: &serde_json::Value) -> u64 {
+        request["id"].as_u64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn responses_are_matched_by_id_out_of_order() {
+        let harness = Harness::new().await;
+        let responder = async {
+            let requests = harness.wait_for_requests(2).await;
+            // Answer the later request first
+            for request in requests.iter().rev() {
+                harness.respond(request_id(request), request["method"].clone());
+            }
+        };
+
+        let (first, second, ()) = tokio::join!(
+            harness.manager.call("first", None),
+            harness.manager.call("second", None),
+            responder
+        );
+
+        assert_eq!(first.unwrap(), "first");
+        assert_eq!(second.unwrap(), "second");
+        assert!(harness.manager.pending.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn timed_out_call_is_forgotten() {
+        let harness = Harness::new().await;
+
+        let result = harness
+            .manager
+            .call_with_timeout("slow", None, Duration::from_millis(20))
+            .await;
+        assert!(matches!(
+            result,
+            Err(SidecarError {
+                code: SidecarErrorCode::Timeout,
+                ..
+            })
+        ));
+        assert!(harness.manager.pending.lock().await.is_empty());
+
+        // The late answer is dropped, and the next call still gets its own response
+        let slow_id = request_id(&harness.requests()[0]);
+        harness.respond(slow_id, "late".into());
+        let responder = async {
+            let requests = harness.wait_for_requests(2).await;
+            harness.respond(request_id(&requests[1]), "fresh".into());
+        };
+        let (result, ()) = tokio::join!(harness.manager.call("next", None), responder);
+        assert_eq!(result.unwrap(), "fresh");
+    }
+
+    #[tokio::test]
+    async fn configured_timeout_removes_pending_entry() {
+        let mut harness = Harness::new().await;
+        harness.manager.set_rpc_timeout(Duration::from_millis(30));
+
+        let check_pending = async {
+            harness.wait_for_requests(1).await;
+            assert_eq!(harness.manager.pending.lock().await.len(), 1);
+        };
+        let (result, ()) = tokio::join!(harness.manager.call("getLiveInfo", None), check_pending);
+
+        assert!(matches!(
+            result,
+            Err(SidecarError {
+                code: SidecarErrorCode::Timeout,
+                ..
+            })
+        ));
+        assert!(harness.manager.pending.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn termination_answers_waiting_calls() {
+        let harness = Harness::new().await;
+        let (termination_tx, mut termination_rx) = mpsc::unbounded_channel();
+        harness
+            .manager
+            .set_termination_handler(termination_tx)
+            .await;
+
+        let terminate = async {
+            harness.wait_for_requests(1).await;
+            harness.terminate("exit code 1");
+        };
+        // Returns well before the 30 s RPC timeout
+        let (result, ()) = tokio::join!(harness.manager.call("hang", None), terminate);
+
+        assert!(matches!(
+            result,
+            Err(SidecarError {
+                code: SidecarErrorCode::NotRunning,
+                ..
+            })
+        ));
+        assert!(harness.manager.pending.lock().await.is_empty());
+        assert!(!harness.manager.is_running().await);
+        assert_eq!(termination_rx.recv().await.unwrap(), "exit code 1");
+    }
+
+    const VIDEO_ID: &str = "dQw4w9WgXcQ";
+
+    #[test]
+    fn extracts_id_from_live_and_shorts_paths() {
+        for url in [
+            "https://www.youtube.com/live/dQw4w9WgXcQ",
+            "https://www.youtube.com/live/dQw4w9WgXcQ?si=abc",
+            "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+        ] {
+            assert_eq!(extract_video_id(url).as_deref(), Ok(VIDEO_ID), "{}", url);
+        }
+    }
+
+    #[test]
+    fn extracts_id_from_mobile_and_mixed_case_hosts() {
+        for url in [
+            "https://m.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://WWW.YouTube.com/watch?v=dQw4w9WgXcQ",
+            "https://YOUTU.BE/dQw4w9WgXcQ",
+        ] {
+            assert_eq!(extract_video_id(url).as_deref(), Ok(VIDEO_ID), "{}", url);
+        }
+    }
+
+    #[test]
+    fn extracts_id_with_trailing_slash() {
+        for url in [
+            "https://www.youtube.com/live/dQw4w9WgXcQ/",
+            "https://youtu.be/dQw4w9WgXcQ/",
+        ] {
+            assert_eq!(extract_video_id(url).as_deref(), Ok(VIDEO_ID), "{}", url);
+        }
+    }
+
+    #[test]
+    fn accepts_bare_id_and_rejects_other_urls() {
+        assert_eq!(extract_video_id("  dQw4w9WgXcQ ").as_deref(), Ok(VIDEO_ID));
+        assert!(extract_video_id("https://www.youtube.com/live/").is_err());
+        assert!(extract_video_id("https://example.com/watch?v=dQw4w9WgXcQ").is_err());
+    }
+
+    #[test]
+    fn stream_buffer_joins_value_split_across_pushes() {
+        let mut buffer = JsonStreamBuffer::default();
+        assert!(buffer.push("{").is_empty());
+        assert!(buffer.push("  \"id\": 1,").is_empty());
+        assert!(buffer.push("  \"result\": true").is_empty());
+        assert_eq!(
+            buffer.push("}"),
+            vec![serde_json::json!({ "id": 1, "result": true })]
+        );
+    }
+
+    #[test]
+    fn stream_buffer_returns_every_value_in_one_push() {
+        let mut buffer = JsonStreamBuffer::default();
+        assert_eq!(
+            buffer.push("{\"id\":1}\n{\"id\":2}\n"),
+            vec![
+                serde_json::json!({ "id": 1 }),
+                serde_json::json!({ "id": 2 })
+            ]
+        );
+    }
+
+    #[test]
+    fn stream_buffer_skips_non_json_line() {
+        let mut buffer = JsonStreamBuffer::default();
+        assert_eq!(
+            buffer.push("Loading YouTube client...\n{\"id\":3}\n"),
+            vec![serde_json::json!({ "id": 3 })]
+        );
+    }
+
+    #[test]
+    fn stream_buffer_discards_oversized_unterminated_value() {
+        let mut buffer = JsonStreamBuffer::default();
+        // An array left open stays incomplete however many lines follow
+        let unterminated = format!("[{}", "1,".repeat(MAX_BUFFERED_OUTPUT / 2 + 1));
+        assert!(buffer.push(&unterminated).is_empty());
+        assert!(buffer.buffer.is_empty());
+        assert_eq!(
+            buffer.push("{\"id\":4}"),
+            vec![serde_json::json!({ "id": 4 })]
+        );
+    }
+
+    #[test]
+    fn live_info_accepts_missing_concurrent_viewers() {
+        let info: LiveInfo = serde_json::from_value(serde_json::json!({
+            "videoId": VIDEO_ID,
+            "title": "Stream",
+            "channelId": "UC123",
+            "channelName": "Channel",
+            "likeCount": 10,
+            "isLive": true,
+        }))
+        .unwrap();
+        assert_eq!(info.concurrent_viewers, None);
+        assert_eq!(info.like_count, Some(10));
+    }
+
+    #[test]
+    fn live_info_accepts_null_fields() {
+        let info: LiveInfo = serde_json::from_value(serde_json::json!({
+            "videoId": null,
+            "title": null,
+            "channelId": "UC123",
+            "channelName": null,
+            "concurrentViewers": null,
+            "likeCount": null,
+            "isLive": true,
+        }))
+        .unwrap();
+        assert_eq!(info.concurrent_viewers, None);
+        assert_eq!(info.like_count, None);
+        assert_eq!(info.title, "");
+        assert!(info.is_live);
+    }
+}
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::config::APP_DIR_NAME;
+use crate::history::Marker;
+use crate::points::{PointState, RawMetrics, SessionAnalytics};
+
+/// 監視セッションの保存ファイル名
+const SESSION_FILE_NAME: &str = "session.json";
+
+/// 直近の配信サマリーの保存ファイル名
+const SUMMARY_FILE_NAME: &str = "last_session_summary.json";
+
+/// 視聴者用ウィンドウの配置の保存ファイル名
+const VIEWER_LAYOUT_FILE_NAME: &str = "viewer_window.json";
+
+/// 保存された監視セッション
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// 監視中の動画 ID
+    pub video_id: Option<String>,
+    /// 監視中のチャンネル ID
+    pub channel_id: Option<String>,
+    /// 保存時点のポイント
+    pub points: PointState,
+    /// 保存時点の最初の配信の指標（再開時に登録者数の基準値とスーパーチャット累計を引き継ぐ）
+    pub metrics: Option<RawMetrics>,
+    /// 同接ボーナスを付与済みか（再開しても二重に付与しない）
+    pub concurrent_bonus_given: bool,
+    /// 保存時刻（UNIX 秒）
+    pub saved_at: u64,
+    /// 保存時点までのマーカー
+    pub markers: Vec<Marker>,
+}
+
+impl SessionState {
+    /// セッションファイルのパス
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(APP_DIR_NAME).join(SESSION_FILE_NAME))
+    }
+
+    /// セッションをディスクに保存し、書き込んだパスを返す
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let path = Self::path().ok_or("Data directory not available")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// 保存されたセッションを読み込む（未保存や読み込めない場合は `None`）
+    pub fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// 保存されたセッションを削除する
+    pub fn clear() -> Result<(), String> {
+        let path = Self::path().ok_or("Data directory not available")?;
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove {}: {}", path.display(), e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `video_id` の監視の続きかどうか
+    pub fn is_for(&self, video_id: &str) -> bool {
+        self.video_id.as_deref() == Some(video_id)
+    }
+
+    /// `video_id` の監視を再開するときに引き継ぐ指標（別の動画のセッションなら `None`）
+    pub fn resumable_metrics(&self, video_id: &str) -> Option<&RawMetrics> {
+        if self.is_for(video_id) {
+            self.metrics.as_ref()
+        } else {
+            None
+        }
+    }
+}
+
+/// 視聴者用ウィンドウの位置とサイズ（論理ピクセル）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowLayout {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl WindowLayout {
+    /// 配置ファイルのパス
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(APP_DIR_NAME).join(VIEWER_LAYOUT_FILE_NAME))
+    }
+
+    /// 保存された配置を読み込む（未保存や読み込めない場合は `None`）
+    pub fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// 配置をディスクに保存する
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("Data directory not available")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// 保存された配置を削除する
+    pub fn clear() -> Result<(), String> {
+        let path = Self::path().ok_or("Data directory not available")?;
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove {}: {}", path.display(), e))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 配信終了時のサマリー（`session-summary` イベントの内容）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub video_id: Option<String>,
+    pub metrics: RawMetrics,
+    pub analytics: SessionAnalytics,
+}
+
+impl SessionSummary {
+    /// サマリーファイルのパス
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(APP_DIR_NAME).join(SUMMARY_FILE_NAME))
+    }
+
+    /// 保存されたサマリーを読み込む（未保存や読み込めない場合は `None`）
+    pub fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// サマリーをディスクに保存する（前回分は上書き）
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("Data directory not available")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_session(video_id: &str, initial_subscribers: i64) -> SessionState {
+        SessionState {
+            video_id: Some(video_id.to_string()),
+            metrics: Some(RawMetrics {
+                superchat_amount: 5000,
+                concurrent_viewers: 300,
+                initial_subscribers,
+                current_subscribers: 1020,
+                membership_count: 2,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn fresh_metrics() -> RawMetrics {
+        RawMetrics {
+            concurrent_viewers: 120,
+            like_count: 40,
+            initial_subscribers: 1050,
+            current_subscribers: 1050,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resuming_same_video_keeps_baseline_and_totals() {
+        let session = saved_session("abc", 1000);
+        let mut metrics = fresh_metrics();
+        metrics.resume_from(session.resumable_metrics("abc").unwrap());
+
+        assert_eq!(metrics.initial_subscribers, 1000);
+        assert_eq!(metrics.superchat_amount, 5000);
+        assert_eq!(metrics.membership_count, 2);
+        // Live values come from the fresh fetch, not the saved session
+        assert_eq!(metrics.concurrent_viewers, 120);
+        assert_eq!(metrics.current_subscribers, 1050);
+    }
+
+    #[test]
+    fn different_video_is_not_resumed() {
+        let session = saved_session("abc", 1000);
+        assert!(session.resumable_metrics("xyz").is_none());
+        assert!(SessionState::default().resumable_metrics("abc").is_none());
+    }
+
+    #[test]
+    fn zero_saved_baseline_is_ignored() {
+        let session = saved_session("abc", 0);
+        let mut metrics = fresh_metrics();
+        metrics.resume_from(session.resumable_metrics("abc").unwrap());
+
+        assert_eq!(metrics.initial_subscribers, 1050);
+        assert_eq!(metrics.superchat_amount, 5000);
+    }
+}
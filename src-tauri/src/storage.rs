@@ -0,0 +1,101 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::history;
+use crate::state::{SessionState, SessionSummary, WindowLayout};
+
+/// ディスクに保存されるデータの種類
+///
+/// Cookie はメモリ上（インポート分）と WebView のプロファイル（ログインウィンドウ分）にしか
+/// 持たないため含まない。ログもファイルには書き出していない。
+pub const STORED_DATA_KINDS: [&str; 5] = ["config", "session", "history", "layout", "summary"];
+
+/// 保存済みデータ 1 件
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredItem {
+    pub kind: String,
+    pub path: String,
+    /// バイト数（ディレクトリの場合は中のファイルの合計）
+    pub size_bytes: u64,
+}
+
+/// 削除結果 1 件
+#[derive(Debug, Clone, Serialize)]
+pub struct ClearResult {
+    pub kind: String,
+    pub path: Option<String>,
+    /// 実際に削除したか（元から存在しなかった場合は `false`）
+    pub removed: bool,
+    pub error: Option<String>,
+}
+
+fn path_for(kind: &str) -> Result<PathBuf, String> {
+    match kind {
+        "config" => Config::path().ok_or_else(|| "Config directory not available".to_string()),
+        "session" => SessionState::path().ok_or_else(|| "Data directory not available".to_string()),
+        "history" => history::history_dir(),
+        "layout" => WindowLayout::path().ok_or_else(|| "Data directory not available".to_string()),
+        "summary" => {
+            SessionSummary::path().ok_or_else(|| "Data directory not available".to_string())
+        }
+        _ => Err(format!("Unknown data kind: {}", kind)),
+    }
+}
+
+fn size_of(path: &Path) -> u64 {
+    if path.is_dir() {
+        std::fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|entry| size_of(&entry.path())).sum())
+            .unwrap_or(0)
+    } else {
+        path.metadata().map(|meta| meta.len()).unwrap_or(0)
+    }
+}
+
+/// ディスク上に存在する保存済みデータを返す
+pub fn list() -> Vec<StoredItem> {
+    STORED_DATA_KINDS
+        .iter()
+        .filter_map(|kind| {
+            let path = path_for(kind).ok()?;
+            path.exists().then(|| StoredItem {
+                kind: kind.to_string(),
+                size_bytes: size_of(&path),
+                path: path.display().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// 指定した種類の保存済みデータを削除する
+pub fn clear(kind: &str) -> ClearResult {
+    let path = match path_for(kind) {
+        Ok(path) => path,
+        Err(e) => {
+            return ClearResult {
+                kind: kind.to_string(),
+                path: None,
+                removed: false,
+                error: Some(e),
+            };
+        }
+    };
+
+    let result = if !path.exists() {
+        Ok(false)
+    } else if path.is_dir() {
+        std::fs::remove_dir_all(&path).map(|_| true)
+    } else {
+        std::fs::remove_file(&path).map(|_| true)
+    };
+
+    ClearResult {
+        kind: kind.to_string(),
+        path: Some(path.display().to_string()),
+        removed: *result.as_ref().unwrap_or(&false),
+        error: result
+            .err()
+            .map(|e| format!("Failed to remove {}: {}", path.display(), e)),
+    }
+}
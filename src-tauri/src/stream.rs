@@ -0,0 +1,82 @@
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::points::RawMetrics;
+use crate::sidecar::{LiveInfo, SidecarManager};
+
+/// 監視中の配信 1 本分の状態
+///
+/// コラボ配信などで複数の配信を同時に監視する場合は配信ごとに作られ、
+/// `AppState.streams` に動画 ID をキーとして保持される。
+pub struct StreamSession {
+    /// 監視中の動画 ID
+    pub video_id: String,
+    /// チャットの取得に使う動画 ID（未指定時は `video_id`）
+    pub chat_id: Option<String>,
+    /// 配信のタイトル
+    pub title: String,
+    /// 最後に取得した `getLiveInfo` の結果
+    pub live_info: RwLock<LiveInfo>,
+    /// 登録者数を取得するチャンネル ID
+    pub channel_id: RwLock<String>,
+    /// `set_monitoring_channel` でチャンネルを変更したか
+    pub channel_overridden: RwLock<bool>,
+    /// ログイン中のチャンネルの正確な登録者数を使えるか
+    ///
+    /// 正確な登録者数はログイン中のチャンネルのものなので、セッションの最初の配信だけが使う。
+    pub allow_exact_count: bool,
+    /// この配信を担当するサイドカー
+    pub sidecar: RwLock<Option<SidecarManager>>,
+    /// この配信の指標（`AppState.raw_metrics` は全配信の合計）
+    pub metrics: RwLock<RawMetrics>,
+    /// 登録者数の基準値を補正済みか（補正はセッション中 1 回のみ）
+    pub baseline_corrected: RwLock<bool>,
+    /// 同時接続者数の急落を 1 回保留しているか
+    pub viewer_dip_pending: RwLock<bool>,
+    /// 高評価数が非公開であることを通知済みか（`likes-unavailable` は配信ごとに 1 回のみ）
+    pub likes_unavailable_notified: RwLock<bool>,
+}
+
+impl StreamSession {
+    pub fn new(
+        video_id: String,
+        chat_id: Option<String>,
+        live_info: LiveInfo,
+        sidecar: SidecarManager,
+        metrics: RawMetrics,
+        allow_exact_count: bool,
+    ) -> Self {
+        Self {
+            video_id,
+            chat_id,
+            title: live_info.title.clone(),
+            channel_id: RwLock::new(live_info.channel_id.clone()),
+            live_info: RwLock::new(live_info),
+            channel_overridden: RwLock::new(false),
+            allow_exact_count,
+            sidecar: RwLock::new(Some(sidecar)),
+            metrics: RwLock::new(metrics),
+            baseline_corrected: RwLock::new(false),
+            viewer_dip_pending: RwLock::new(false),
+            likes_unavailable_notified: RwLock::new(false),
+        }
+    }
+
+    pub async fn info(&self) -> StreamInfo {
+        StreamInfo {
+            video_id: self.video_id.clone(),
+            title: self.title.clone(),
+            channel_id: self.channel_id.read().await.clone(),
+            metrics: self.metrics.read().await.clone(),
+        }
+    }
+}
+
+/// 画面に表示する配信ごとの情報
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamInfo {
+    pub video_id: String,
+    pub title: String,
+    pub channel_id: String,
+    pub metrics: RawMetrics,
+}
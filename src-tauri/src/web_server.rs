@@ -1,35 +1,173 @@
 use axum::{
-    Router,
-    extract::State,
-    response::{Html, Sse, sse::Event},
-    routing::get,
+    Json, Router,
+    extract::{
+        Path, Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderMap, StatusCode, header},
+    response::{Html, Response, Sse, sse::Event},
+    routing::{get, post},
 };
 use futures::stream::Stream;
-use std::{convert::Infallible, net::TcpListener, sync::Arc, time::Duration};
+use std::{collections::HashMap, convert::Infallible, net::TcpListener, sync::Arc, time::Duration};
 use tokio::sync::broadcast;
 use tokio_stream::{StreamExt, wrappers::BroadcastStream};
 use tower_http::cors::CorsLayer;
 
-use crate::config::PointsConfig;
-use crate::points::{PointState, RawMetrics};
+use crate::AppState;
+use crate::config::{OverlayConfig, PointsConfig, ProgressMetric, SourceVisibility};
+use crate::formula::Formula;
+use crate::points::{GoalEstimate, PointState, RawMetrics};
 
-#[derive(Clone, serde::Serialize)]
+#[derive(Clone, Default, serde::Serialize)]
 pub struct PointsPayload {
     pub points: PointState,
     pub metrics: RawMetrics,
     pub config: PointsConfig,
+    /// 監視中かどうか（停止中に手動でポイントを追加した場合は `false`）
+    pub is_monitoring: bool,
+    pub is_stale: bool,
+    /// 一時停止中か（停止中はポイントが増えない）
+    pub is_paused: bool,
+    /// 直近の登録者数が正確な値か（`false` なら公開されている概数）
+    pub using_exact_subscribers: bool,
+    /// 目標の進捗に数えるポイント（`overlay.goal_counts_manual` を反映）
+    pub goal_points: i64,
+    /// 進捗バーが追う指標
+    pub progress_metric: ProgressMetric,
+    /// 進捗バーの現在値（`progress_metric` の値）
+    pub progress_value: i64,
+    /// 進捗バーの目標（`progress_metric` の単位）。段階的な目標では現在の目標
+    pub goal: i64,
+    /// 現在の目標の次の目標
+    pub next_goal: Option<i64>,
+    /// 達成済みの目標の数
+    pub goals_reached: usize,
+    /// 目標の総数
+    pub goal_count: usize,
+    /// 目標達成までの見込み（`overlay.show_time_to_goal` が有効な場合のみ）
+    pub time_to_goal: Option<GoalEstimate>,
+}
+
+/// 一時的にオーバーレイに表示するメッセージ
+#[derive(Clone, serde::Serialize)]
+pub struct OverlayMessage {
+    pub text: String,
+    pub duration_ms: u64,
+}
+
+/// オーバーレイがリロードせずに反映する表示設定（SSE の `overlay-config` イベント）
+///
+/// ブラウザソースのクエリで上書きされている項目は、そのオーバーレイでは無視される。
+#[derive(Clone, serde::Serialize)]
+pub struct OverlayLiveConfig {
+    pub animation_duration_ms: u64,
+    pub popup_template: String,
+    pub flash_threshold: i64,
+    pub celebration_threshold: Option<i64>,
+}
+
+impl OverlayLiveConfig {
+    pub fn new(overlay: &OverlayConfig) -> Self {
+        Self {
+            animation_duration_ms: overlay.animation_duration_ms,
+            popup_template: overlay.popup_template.clone(),
+            flash_threshold: overlay.flash_threshold,
+            celebration_threshold: overlay.celebration_threshold,
+        }
+    }
+}
+
+/// Web クライアントに配信するイベント
+#[derive(Clone)]
+pub enum WebEvent {
+    /// ポイント更新（SSE の `points` / `breakdown` イベント）
+    Points(Box<PointsPayload>),
+    /// 告知メッセージ（SSE の `message` イベント）
+    Message(OverlayMessage),
+    /// 合計ポイントが初めて上限に達した（SSE の `cap-reached` イベント、データは上限値）
+    CapReached(i64),
+    /// 監視の一時停止・再開（SSE の `monitoring-paused` イベント、データは一時停止中か）
+    Paused(bool),
+    /// 表示設定の変更（SSE の `overlay-config` イベント）
+    OverlayConfig(OverlayLiveConfig),
+}
+
+/// オーバーレイのレイアウト（ブラウザソースに指定するページ）
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct OverlayLayout {
+    pub name: &'static str,
+    /// サーバー上のパス
+    pub path: &'static str,
+    pub description: &'static str,
+}
+
+/// 提供しているレイアウトの一覧（ルートを追加したらここにも追加する）
+pub const OVERLAY_LAYOUTS: &[OverlayLayout] = &[OverlayLayout {
+    name: "standard",
+    path: "/",
+    description: "合計ポイント、目標の進捗バー、スーパーチャット・同接・高評価・新規登録者の内訳",
+}];
+
+/// OBS 用サーバーの待ち受けアドレス（このマシンからのみ接続可能）
+pub const BIND_HOST: &str = "127.0.0.1";
+
+/// OBS 用サーバーの CORS ポリシー（`CorsLayer::permissive()`）
+pub const CORS_POLICY: &str = "permissive";
+
+#[derive(Clone)]
+struct ServerState {
+    app_state: Arc<AppState>,
+    app: tauri::AppHandle,
+    /// Port the server listens on, for recognizing the overlay's own pages
+    port: u16,
 }
 
 pub struct WebServer {
     port: u16,
-    tx: broadcast::Sender<PointsPayload>,
+    state: ServerState,
 }
 
 impl WebServer {
-    pub fn new(tx: broadcast::Sender<PointsPayload>) -> Option<Self> {
-        // Find available port in range 1430-1460 (avoid 1420 used by vite dev server)
-        let port = (1430..=1460).find(|&p| TcpListener::bind(("127.0.0.1", p)).is_ok())?;
-        Some(Self { port, tx })
+    /// Uses `preferred_port` when it is free, otherwise the first free port in 1430-1460
+    /// (avoiding 1420 used by the vite dev server). Returns `None` when none is available.
+    pub fn new(
+        app_state: Arc<AppState>,
+        app: tauri::AppHandle,
+        preferred_port: Option<u16>,
+    ) -> Option<Self> {
+        let is_free = |port: u16| TcpListener::bind((BIND_HOST, port)).is_ok();
+        let first_free = || (1430..=1460).find(|&p| is_free(p));
+        let port = match preferred_port {
+            Some(port) if is_free(port) => {
+                println!("Using configured web server port {}", port);
+                port
+            }
+            Some(port) => {
+                let fallback = first_free()?;
+                println!(
+                    "Configured web server port {} is in use, using {} instead",
+                    port, fallback
+                );
+                fallback
+            }
+            None => {
+                let port = first_free()?;
+                println!(
+                    "No web server port configured, using first free port {}",
+                    port
+                );
+                port
+            }
+        };
+        Some(Self {
+            port,
+            state: ServerState {
+                app_state,
+                app,
+                port,
+            },
+        })
     }
 
     pub fn url(&self) -> String {
@@ -37,14 +175,18 @@ impl WebServer {
     }
 
     pub async fn start(self) -> Result<(), String> {
-        let addr = format!("127.0.0.1:{}", self.port);
-        let tx = Arc::new(self.tx);
+        let addr = format!("{}:{}", BIND_HOST, self.port);
 
         let app = Router::new()
             .route("/", get(serve_viewer))
             .route("/events", get(sse_handler))
+            .route("/ws", get(ws_handler))
+            .route("/points", get(current_points))
+            .route("/health", get(health))
+            .route("/profiles", get(list_profiles))
+            .route("/profile/:name", post(switch_profile))
             .layer(CorsLayer::permissive())
-            .with_state(tx);
+            .with_state(self.state);
 
         let listener = tokio::net::TcpListener::bind(&addr)
             .await
@@ -60,29 +202,447 @@ impl WebServer {
     }
 }
 
-async fn serve_viewer() -> Html<&'static str> {
-    Html(VIEWER_HTML)
+/// Interval of the SSE keep-alive comment and the WebSocket ping
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Options injected into the viewer page as `OVERLAY_OPTIONS`
+#[derive(serde::Serialize)]
+struct OverlayOptions {
+    popup_template: String,
+    effect_cooldown_ms: u64,
+    animation_duration_ms: u64,
+    max_popups: usize,
+    flash_threshold: i64,
+    celebration_threshold: Option<i64>,
+    goal: i64,
+    effects: bool,
+    visible: SourceVisibility,
+}
+
+impl OverlayOptions {
+    /// Configured overlay options, with query params overriding them per browser source
+    fn new(overlay: &OverlayConfig, query: &HashMap<String, String>) -> Self {
+        Self {
+            popup_template: query
+                .get("popup_template")
+                .cloned()
+                .unwrap_or_else(|| overlay.popup_template.clone()),
+            effect_cooldown_ms: query_or(query, "effect_cooldown_ms", overlay.effect_cooldown_ms),
+            animation_duration_ms: query_or(
+                query,
+                "animation_duration_ms",
+                overlay.animation_duration_ms,
+            ),
+            max_popups: query_or(query, "max_popups", overlay.max_popups),
+            flash_threshold: query_or(query, "flash_threshold", overlay.flash_threshold),
+            celebration_threshold: query
+                .get("celebration_threshold")
+                .and_then(|value| value.parse().ok())
+                .or(overlay.celebration_threshold),
+            goal: query_or(query, "goal", overlay.goal),
+            effects: query_or(query, "effects", overlay.superchat_effects),
+            visible: source_visibility(overlay.visible, query),
+        }
+    }
+}
+
+/// Overlay page. Query params override the configured overlay options for one browser source:
+/// `popup_template`, `effect_cooldown_ms`, `animation_duration_ms`, `max_popups`, `flash_threshold`, `celebration_threshold`,
+/// `goal`, `hide`/`show` (comma-separated sources), and `effects=false` to turn off the flash and celebration effects (e.g. a minimal counter).
+/// `theme` (`dark`, `light` or `minimal`) picks the color scheme.
+async fn serve_viewer(
+    State(state): State<ServerState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Html<String> {
+    let overlay = state.app_state.config.read().await.overlay.clone();
+    let options = OverlayOptions::new(&overlay, &query);
+    let theme = theme_css(query.get("theme").map(String::as_str));
+    Html(render_viewer(&options, &overlay.title, theme))
+}
+
+/// Renders the overlay as a standalone page that streams from `server_url`
+/// (e.g. `http://localhost:1430`), so it also works when opened from a file or another host.
+pub fn render_standalone_viewer(overlay: &OverlayConfig, server_url: &str) -> String {
+    let options = OverlayOptions::new(overlay, &HashMap::new());
+    let events_url = format!("{}/events", server_url.trim_end_matches('/'));
+    let events_url_json = serde_json::to_string(&events_url)
+        .unwrap_or_default()
+        .replace("</", "<\\/");
+    render_viewer(&options, &overlay.title, theme_css(None)).replace("'/events'", &events_url_json)
+}
+
+/// CSS variables for an overlay theme. Unknown or missing names keep the default dark look.
+fn theme_css(theme: Option<&str>) -> &'static str {
+    match theme {
+        Some("light") => {
+            ":root { --accent: #d6336c; --bg: linear-gradient(135deg, rgba(255, 255, 255, 0.95) 0%, rgba(236, 240, 248, 0.95) 100%); \
+             --border: rgba(214, 51, 108, 0.4); --glow: 0 0 20px rgba(0, 0, 0, 0.15); --panel: rgba(0, 0, 0, 0.05); \
+             --text: #1a1a2e; --muted: #555; --subtle: #777; }"
+        }
+        Some("minimal") => {
+            ":root { --accent: #e94560; --bg: transparent; --border: transparent; --glow: none; --panel: transparent; \
+             --text: #fff; --muted: #ccc; --subtle: #aaa; }"
+        }
+        _ => {
+            ":root { --accent: #e94560; --bg: linear-gradient(135deg, rgba(26, 26, 46, 0.95) 0%, rgba(22, 33, 62, 0.95) 100%); \
+             --border: rgba(233, 69, 96, 0.5); \
+             --glow: 0 0 20px rgba(233, 69, 96, 0.3), 0 0 40px rgba(233, 69, 96, 0.1), inset 0 0 60px rgba(0, 0, 0, 0.3); \
+             --panel: rgba(0, 0, 0, 0.3); --text: #fff; --muted: #888; --subtle: #666; }"
+        }
+    }
+}
+
+/// Applies the comma-separated `hide` and `show` query params (e.g. `hide=likes,viewers`)
+fn source_visibility(
+    mut visible: SourceVisibility,
+    query: &HashMap<String, String>,
+) -> SourceVisibility {
+    for (key, value) in [("hide", false), ("show", true)] {
+        for source in query.get(key).into_iter().flat_map(|list| list.split(',')) {
+            // Unknown names are ignored like other invalid overlay params
+            let _ = visible.set(source.trim(), value);
+        }
+    }
+    visible
+}
+
+/// Parses a query param, falling back to the default when absent or invalid
+fn query_or<T: std::str::FromStr>(query: &HashMap<String, String>, key: &str, default: T) -> T {
+    query
+        .get(key)
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
 }
 
+fn render_viewer(options: &OverlayOptions, title: &str, theme: &str) -> String {
+    // Escape "</" so injected strings cannot close the script tag
+    let options_json = serde_json::to_string(options)
+        .unwrap_or_else(|_| "{}".to_string())
+        .replace("</", "<\\/");
+    VIEWER_HTML
+        .replace("__OVERLAY_THEME__", theme)
+        .replace("__OVERLAY_TITLE__", &escape_html(title))
+        .replace("__OVERLAY_OPTIONS__", &options_json)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Per-source points, sent as a separate `breakdown` SSE event only when they change
+#[derive(Clone, PartialEq, serde::Serialize)]
+struct PointsBreakdown {
+    superchat: i64,
+    concurrent: i64,
+    likes: i64,
+    subscribers: i64,
+    manual: i64,
+    visitor: i64,
+    membership: i64,
+    bonus: i64,
+}
+
+impl From<&PointState> for PointsBreakdown {
+    fn from(points: &PointState) -> Self {
+        Self {
+            superchat: points.superchat,
+            concurrent: points.concurrent,
+            likes: points.likes,
+            subscribers: points.subscribers,
+            manual: points.manual,
+            visitor: points.visitor,
+            membership: points.membership,
+            bonus: points.bonus,
+        }
+    }
+}
+
+/// Data of a `points` SSE event. Clients that take the per-source points from `breakdown`
+/// get only the total in `points`.
+fn points_event_data(payload: &PointsPayload, total_only: bool) -> String {
+    let Ok(mut data) = serde_json::to_value(payload) else {
+        return String::new();
+    };
+    if total_only {
+        data["points"] = serde_json::json!({ "total": payload.points.total });
+    }
+    data.to_string()
+}
+
+/// SSE stream of points updates. An optional `formula` query param recomputes `total`
+/// for this connection only (see [`Formula`]); invalid formulas are rejected with 400.
+/// `progress_metric` and `goal` likewise override the progress bar for this connection.
+/// With `breakdown=1`, the per-source points are sent as a `breakdown` event when they change
+/// and the `points` event carries only the total.
 async fn sse_handler(
-    State(tx): State<Arc<broadcast::Sender<PointsPayload>>>,
-) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let rx = tx.subscribe();
-    let stream = BroadcastStream::new(rx).filter_map(|result: Result<PointsPayload, _>| {
-        result.ok().map(|payload| {
-            Ok(Event::default()
+    State(state): State<ServerState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let formula = query
+        .get("formula")
+        .map(|source| Formula::parse(source))
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid formula: {}", e)))?;
+    let progress_metric = query
+        .get("progress_metric")
+        .map(|metric| metric.parse::<ProgressMetric>())
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let goal = query.get("goal").and_then(|goal| goal.parse::<i64>().ok());
+    let send_breakdown = query
+        .get("breakdown")
+        .is_some_and(|flag| flag == "1" || flag == "true");
+
+    let rx = state.app_state.web_broadcast.subscribe();
+    let mut last_breakdown: Option<PointsBreakdown> = None;
+    let stream = BroadcastStream::new(rx)
+        .filter_map(|result: Result<WebEvent, _>| result.ok())
+        .map(move |event| {
+            let mut payload = match event {
+                WebEvent::Points(payload) => *payload,
+                WebEvent::Message(message) => {
+                    return tokio_stream::iter(vec![Ok(Event::default()
+                        .event("message")
+                        .data(serde_json::to_string(&message).unwrap_or_default()))]);
+                }
+                WebEvent::CapReached(cap) => {
+                    return tokio_stream::iter(vec![Ok(Event::default()
+                        .event("cap-reached")
+                        .data(cap.to_string()))]);
+                }
+                WebEvent::Paused(paused) => {
+                    return tokio_stream::iter(vec![Ok(Event::default()
+                        .event("monitoring-paused")
+                        .data(paused.to_string()))]);
+                }
+                WebEvent::OverlayConfig(config) => {
+                    return tokio_stream::iter(vec![Ok(Event::default()
+                        .event("overlay-config")
+                        .data(serde_json::to_string(&config).unwrap_or_default()))]);
+                }
+            };
+
+            // Fall back to the configured total if the formula is not finite (e.g. division by 0)
+            if let Some(total) = formula
+                .as_ref()
+                .and_then(|formula| formula.evaluate(&payload.metrics, &payload.points))
+            {
+                payload.points.total = total;
+                payload.points.apply_cap(payload.config.total_cap);
+            }
+            if let Some(metric) = progress_metric {
+                payload.progress_metric = metric;
+                payload.progress_value = metric.value(payload.goal_points, &payload.metrics);
+            }
+            // A goal override replaces the stretch goals with a single goal
+            if let Some(goal) = goal.filter(|goal| *goal > 0) {
+                payload.goal = goal;
+                payload.next_goal = None;
+                payload.goals_reached = usize::from(payload.progress_value >= goal);
+                payload.goal_count = 1;
+            }
+
+            // The breakdown goes first so the total never arrives ahead of its sources
+            let mut events = Vec::new();
+            let breakdown = PointsBreakdown::from(&payload.points);
+            if send_breakdown && last_breakdown.as_ref() != Some(&breakdown) {
+                events.push(Ok(Event::default()
+                    .event("breakdown")
+                    .data(serde_json::to_string(&breakdown).unwrap_or_default())));
+                last_breakdown = Some(breakdown);
+            }
+            events.push(Ok(Event::default()
                 .event("points")
-                .data(serde_json::to_string(&payload).unwrap_or_default()))
-        })
-    });
+                .data(points_event_data(&payload, send_breakdown))));
+            tokio_stream::iter(events)
+        });
 
-    Sse::new(stream).keep_alive(
+    Ok(Sse::new(futures::StreamExt::flatten(stream)).keep_alive(
         axum::response::sse::KeepAlive::new()
-            .interval(Duration::from_secs(15))
+            .interval(KEEP_ALIVE_INTERVAL)
             .text("ping"),
+    ))
+}
+
+/// The latest points payload, for clients that poll instead of streaming.
+/// Before the first update (e.g. before monitoring starts) every field is zero/default.
+async fn current_points(State(state): State<ServerState>) -> Json<PointsPayload> {
+    Json(
+        state
+            .app_state
+            .latest_points
+            .read()
+            .await
+            .clone()
+            .unwrap_or_default(),
     )
 }
 
+#[derive(serde::Serialize)]
+struct HealthStatus {
+    status: &'static str,
+    monitoring: bool,
+}
+
+/// Readiness check for scripts that wait for the server before adding the OBS browser source
+async fn health(State(state): State<ServerState>) -> Json<HealthStatus> {
+    Json(HealthStatus {
+        status: "ok",
+        monitoring: *state.app_state.is_monitoring.read().await,
+    })
+}
+
+/// WebSocket stream of points updates. Each payload is sent as a JSON text frame, the same
+/// as the SSE `points` event; the other overlay events are only sent over SSE.
+async fn ws_handler(State(state): State<ServerState>, ws: WebSocketUpgrade) -> Response {
+    let rx = state.app_state.web_broadcast.subscribe();
+    ws.on_upgrade(move |socket| forward_points(socket, rx))
+}
+
+/// Forwards points payloads until the client goes away; dropping `rx` unsubscribes it
+async fn forward_points(mut socket: WebSocket, mut rx: broadcast::Receiver<WebEvent>) {
+    let mut ping = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+    // The first tick completes immediately
+    ping.tick().await;
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(WebEvent::Points(payload)) => {
+                    let text = serde_json::to_string(&*payload).unwrap_or_default();
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = ping.tick() => {
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Checks the optional auth token from the `Authorization` header or `token` query param
+async fn authorize(
+    state: &ServerState,
+    headers: &HeaderMap,
+    query: &HashMap<String, String>,
+) -> Result<(), StatusCode> {
+    let config = state.app_state.config.read().await;
+    let Some(expected) = config.server.auth_token.as_deref() else {
+        return Ok(());
+    };
+
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let provided = bearer.or(query.get("token").map(String::as_str));
+
+    if provided == Some(expected) {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// Checks a state-changing request. With an auth token configured it is required; without
+/// one, browsers may only send the request from the overlay's own pages, so a site open in
+/// the streamer's browser cannot change anything.
+async fn authorize_change(
+    state: &ServerState,
+    headers: &HeaderMap,
+    query: &HashMap<String, String>,
+) -> Result<(), StatusCode> {
+    if state
+        .app_state
+        .config
+        .read()
+        .await
+        .server
+        .auth_token
+        .is_some()
+    {
+        return authorize(state, headers, query).await;
+    }
+    match headers.get(header::ORIGIN) {
+        // Tools outside a browser (OBS scripts, curl) send no Origin
+        None => Ok(()),
+        Some(origin)
+            if origin
+                .to_str()
+                .is_ok_and(|origin| is_overlay_origin(origin, state.port)) =>
+        {
+            Ok(())
+        }
+        Some(_) => Err(StatusCode::FORBIDDEN),
+    }
+}
+
+/// Whether `origin` is a page served by this server
+fn is_overlay_origin(origin: &str, port: u16) -> bool {
+    [BIND_HOST, "localhost"]
+        .iter()
+        .any(|host| origin == format!("http://{}:{}", host, port))
+}
+
+#[derive(serde::Serialize)]
+struct ProfilesResponse {
+    active: Option<String>,
+    profiles: Vec<String>,
+}
+
+async fn profiles_response(state: &ServerState) -> ProfilesResponse {
+    let config = state.app_state.config.read().await;
+    ProfilesResponse {
+        active: config.active_profile.clone(),
+        profiles: config.profiles.keys().cloned().collect(),
+    }
+}
+
+async fn list_profiles(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<ProfilesResponse>, StatusCode> {
+    authorize(&state, &headers, &query).await?;
+    Ok(Json(profiles_response(&state).await))
+}
+
+async fn switch_profile(
+    State(state): State<ServerState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<ProfilesResponse>, StatusCode> {
+    authorize_change(&state, &headers, &query).await?;
+
+    {
+        let mut config = state.app_state.config.write().await;
+        config
+            .switch_profile(&name)
+            .map_err(|_| StatusCode::NOT_FOUND)?;
+    }
+    println!("Switched to profile: {}", name);
+
+    // Recalculate with the new rates
+    crate::emit_points(&state.app_state, &state.app).await;
+
+    Ok(Json(profiles_response(&state).await))
+}
+
 const VIEWER_HTML: &str = r##"<!DOCTYPE html>
 <html lang="ja">
 <head>
@@ -90,6 +650,7 @@ const VIEWER_HTML: &str = r##"<!DOCTYPE html>
 <meta name="viewport" content="width=device-width, initial-scale=1.0">
 <title>YT Point Viewer</title>
 <style>
+__OVERLAY_THEME__
 * { margin: 0; padding: 0; box-sizing: border-box; }
 body {
   font-family: 'Segoe UI', system-ui, sans-serif;
@@ -101,14 +662,14 @@ body {
   width: 100%;
   height: 100vh;
   padding: 20px;
-  background: linear-gradient(135deg, rgba(26, 26, 46, 0.95) 0%, rgba(22, 33, 62, 0.95) 100%);
-  border: 2px solid rgba(233, 69, 96, 0.5);
-  box-shadow: 0 0 20px rgba(233, 69, 96, 0.3), 0 0 40px rgba(233, 69, 96, 0.1), inset 0 0 60px rgba(0, 0, 0, 0.3);
+  background: var(--bg);
+  border: 2px solid var(--border);
+  box-shadow: var(--glow);
 }
 .header { text-align: center; margin-bottom: 16px; }
 .title {
   font-size: 14px;
-  color: #888;
+  color: var(--muted);
   text-transform: uppercase;
   letter-spacing: 3px;
 }
@@ -139,7 +700,7 @@ body {
   display: flex;
   justify-content: space-between;
   font-size: 12px;
-  color: #888;
+  color: var(--muted);
   margin-bottom: 6px;
 }
 .progress-bar {
@@ -152,7 +713,7 @@ body {
 }
 .progress-fill {
   height: 100%;
-  background: linear-gradient(90deg, #e94560, #ff6b6b, #ffd700);
+  background: linear-gradient(90deg, var(--accent), #ff6b6b, #ffd700);
   border-radius: 12px;
   transition: width 0.5s ease-out;
   position: relative;
@@ -184,14 +745,16 @@ body {
 }
 .stats { display: grid; grid-template-columns: 1fr 1fr; gap: 10px; }
 .stat-item {
-  background: rgba(0, 0, 0, 0.3);
+  background: var(--panel);
   padding: 10px;
   border-radius: 8px;
   border: 1px solid rgba(255, 255, 255, 0.05);
 }
 .stat-icon { font-size: 16px; margin-bottom: 4px; }
-.stat-value { font-size: 20px; font-weight: 600; color: #fff; }
-.stat-label { font-size: 10px; color: #666; text-transform: uppercase; letter-spacing: 1px; }
+.stat-value { font-size: 20px; font-weight: 600; color: var(--text); }
+.stat-label { font-size: 10px; color: var(--subtle); text-transform: uppercase; letter-spacing: 1px; }
+.extra-stats { margin-top: 10px; }
+.extra-stats:empty { display: none; }
 .point-popup {
   position: fixed;
   font-size: 24px;
@@ -218,6 +781,41 @@ body {
   0% { opacity: 1; }
   100% { opacity: 0; }
 }
+.celebration-effect {
+  position: fixed;
+  top: 0; left: 0; right: 0; bottom: 0;
+  background: radial-gradient(circle, rgba(255, 215, 0, 0.6) 0%, rgba(233, 69, 96, 0.3) 50%, transparent 80%);
+  animation: celebrate 1.5s ease-out forwards;
+  pointer-events: none;
+  z-index: 999;
+}
+@keyframes celebrate {
+  0% { opacity: 1; transform: scale(0.8); }
+  30% { opacity: 1; transform: scale(1.1); }
+  100% { opacity: 0; transform: scale(1.3); }
+}
+.overlay-message {
+  position: fixed;
+  left: 50%;
+  bottom: 20px;
+  transform: translateX(-50%);
+  max-width: 90%;
+  padding: 10px 20px;
+  background: rgba(0, 0, 0, 0.75);
+  border: 1px solid rgba(255, 215, 0, 0.6);
+  border-radius: 8px;
+  color: #ffd700;
+  font-size: 18px;
+  font-weight: 700;
+  text-align: center;
+  pointer-events: none;
+  z-index: 1001;
+  animation: messageIn 0.3s ease-out;
+}
+@keyframes messageIn {
+  0% { opacity: 0; transform: translate(-50%, 20px); }
+  100% { opacity: 1; transform: translate(-50%, 0); }
+}
 .connection-status {
   position: fixed;
   top: 5px;
@@ -232,12 +830,13 @@ body {
 </head>
 <body>
 <div class="viewer-container">
-  <div class="header"><div class="title">LIVE POINTS</div></div>
+  <div class="header"><div class="title">__OVERLAY_TITLE__</div></div>
   <div class="score-section"><div class="score" id="score">0</div></div>
   <div class="progress-section">
     <div class="progress-label">
-      <span>Progress</span>
-      <span id="progress-text">0 / 1,000</span>
+      <span id="goal-label">Progress</span>
+      <span id="goal-eta"></span>
+      <span id="progress-text">0</span>
     </div>
     <div class="progress-bar">
       <div class="progress-fill" id="progress-fill" style="width: 0%">
@@ -264,44 +863,88 @@ body {
     <div class="stat-item">
       <div class="stat-icon">🔔</div>
       <div class="stat-value" id="subs">0</div>
-      <div class="stat-label">New Subs</div>
+      <div class="stat-label" id="subs-label">New Subs</div>
     </div>
   </div>
+  <div class="stats extra-stats" id="extra-stats"></div>
 </div>
 <div class="connection-status" id="status">Connecting...</div>
 <script>
-const TARGET_POINTS = 1000;
+const OVERLAY_OPTIONS = __OVERLAY_OPTIONS__;
+// Updated from each points event so goal changes apply without a reload
+let targetPoints = OVERLAY_OPTIONS.goal;
 let currentScore = 0;
 let displayedScore = 0;
+// Points shown in the total but excluded from goal progress
+let goalOffset = 0;
+let progressMetric = 'total';
+let progressValue = 0;
 let animationFrame = null;
+let lastEffectAt = 0;
 
 function formatNumber(n) {
   return n.toLocaleString();
 }
 
-function updateDisplay(points, metrics) {
+function renderProgress() {
+  // Points progress follows the animated score; other metrics jump straight to their value
+  const goalScore = progressMetric === 'total'
+    ? Math.max(displayedScore - goalOffset, 0)
+    : Math.max(progressValue, 0);
+  const progress = Math.min((goalScore / targetPoints) * 100, 100);
+  document.getElementById('progress-fill').style.width = progress + '%';
+  document.getElementById('progress-text').textContent =
+    formatNumber(goalScore) + ' / ' + formatNumber(targetPoints);
+}
+
+function renderGoalEta(estimate) {
+  const eta = document.getElementById('goal-eta');
+  if (estimate && estimate.status === 'estimated') {
+    eta.textContent = '~' + Math.max(Math.round(estimate.seconds / 60), 1) + ' min to goal';
+  } else {
+    eta.textContent = '';
+  }
+}
+
+function renderGoalLabel(reached, count) {
+  const label = document.getElementById('goal-label');
+  if (count <= 1) {
+    label.textContent = 'Progress';
+  } else if (reached >= count) {
+    label.textContent = 'Goal ' + count + ' \u2713';
+  } else if (reached > 0) {
+    label.textContent = 'Goal ' + reached + ' \u2713 \u2014 on to Goal ' + (reached + 1);
+  } else {
+    label.textContent = 'Goal 1';
+  }
+}
+
+function updateDisplay(points, metrics, progress, goal) {
   const prevScore = currentScore;
   currentScore = points.total;
+  progressMetric = progress.metric;
+  progressValue = progress.value;
+  goalOffset = points.total - progress.value;
+  targetPoints = goal;
+  renderProgress();
 
-  // Animate score
+  // Animate score from where it is now to the new total over the configured duration
   if (animationFrame) cancelAnimationFrame(animationFrame);
-  function animate() {
-    if (displayedScore === currentScore) return;
-    const diff = currentScore - displayedScore;
-    const step = Math.ceil(Math.abs(diff) / 10) || 1;
-    displayedScore = diff > 0
-      ? Math.min(displayedScore + step, currentScore)
-      : Math.max(displayedScore - step, currentScore);
+  const from = displayedScore;
+  const startedAt = performance.now();
+  function animate(now) {
+    const duration = OVERLAY_OPTIONS.animation_duration_ms;
+    const t = duration > 0 ? Math.min((now - startedAt) / duration, 1) : 1;
+    // Ease out so large jumps settle gently
+    const eased = 1 - Math.pow(1 - t, 3);
+    displayedScore = t >= 1 ? currentScore : Math.round(from + (currentScore - from) * eased);
     document.getElementById('score').textContent = formatNumber(displayedScore);
-    const progress = Math.min((displayedScore / TARGET_POINTS) * 100, 100);
-    document.getElementById('progress-fill').style.width = progress + '%';
-    document.getElementById('progress-text').textContent =
-      formatNumber(displayedScore) + ' / ' + formatNumber(TARGET_POINTS);
+    renderProgress();
     if (displayedScore !== currentScore) {
       animationFrame = requestAnimationFrame(animate);
     }
   }
-  animate();
+  animate(startedAt);
 
   // Update stats
   document.getElementById('superchat').textContent = formatNumber(metrics.superchat_amount);
@@ -310,38 +953,98 @@ function updateDisplay(points, metrics) {
   document.getElementById('subs').textContent = formatNumber(
     metrics.current_subscribers - metrics.initial_subscribers
   );
+  updateExtraStats(metrics.extra_metrics || {});
 
   // Show popup on increase
   const diff = currentScore - prevScore;
   if (diff > 0 && prevScore > 0) {
     showPopup(diff);
-    if (diff >= 10) showSuperEffect();
+    if (!OVERLAY_OPTIONS.effects) return;
+    const celebration = OVERLAY_OPTIONS.celebration_threshold;
+    if (celebration !== null && diff >= celebration) showCelebration();
+    else if (diff >= OVERLAY_OPTIONS.flash_threshold) showSuperEffect();
   }
 }
 
+function updateExtraStats(extraMetrics) {
+  const container = document.getElementById('extra-stats');
+  container.replaceChildren(...Object.keys(extraMetrics).sort().map((name) => {
+    const item = document.createElement('div');
+    item.className = 'stat-item';
+    const value = document.createElement('div');
+    value.className = 'stat-value';
+    value.textContent = formatNumber(extraMetrics[name]);
+    const label = document.createElement('div');
+    label.className = 'stat-label';
+    label.textContent = name;
+    item.append(value, label);
+    return item;
+  }));
+}
+
+// Popups still on screen, oldest first
+const activePopups = [];
+
 function showPopup(amount) {
+  // Drop the oldest popups so bursts cannot pile up DOM nodes on slow machines
+  while (activePopups.length >= Math.max(OVERLAY_OPTIONS.max_popups, 1)) {
+    activePopups.shift().remove();
+  }
   const popup = document.createElement('div');
   popup.className = 'point-popup';
-  popup.textContent = '+' + amount;
+  popup.textContent = OVERLAY_OPTIONS.popup_template.replaceAll('{amount}', amount);
   popup.style.left = (50 + (Math.random() - 0.5) * 30) + '%';
   popup.style.top = '40%';
   document.body.appendChild(popup);
-  setTimeout(() => popup.remove(), 1500);
+  activePopups.push(popup);
+  setTimeout(() => {
+    popup.remove();
+    const index = activePopups.indexOf(popup);
+    if (index !== -1) activePopups.splice(index, 1);
+  }, 1500);
 }
 
 function showSuperEffect() {
+  // Coalesce bursts into a single flash per cooldown window
+  const now = Date.now();
+  if (now - lastEffectAt < OVERLAY_OPTIONS.effect_cooldown_ms) return;
+  lastEffectAt = now;
+
   const effect = document.createElement('div');
   effect.className = 'superchat-effect';
   document.body.appendChild(effect);
   setTimeout(() => effect.remove(), 500);
 }
 
+function showCelebration() {
+  const effect = document.createElement('div');
+  effect.className = 'celebration-effect';
+  document.body.appendChild(effect);
+  setTimeout(() => effect.remove(), 1500);
+}
+
+let messageTimer = null;
+
+function showMessage(text, durationMs) {
+  let banner = document.getElementById('overlay-message');
+  if (!banner) {
+    banner = document.createElement('div');
+    banner.id = 'overlay-message';
+    banner.className = 'overlay-message';
+    document.body.appendChild(banner);
+  }
+  banner.textContent = text;
+  if (messageTimer) clearTimeout(messageTimer);
+  messageTimer = setTimeout(() => banner.remove(), durationMs);
+}
+
 function connect() {
   const status = document.getElementById('status');
   status.textContent = 'Connecting...';
   status.className = 'connection-status';
 
-  const eventSource = new EventSource('/events');
+  // Forward the page's query (e.g. a per-scene `formula`) to the event stream
+  const eventSource = new EventSource('/events' + location.search);
 
   eventSource.onopen = () => {
     status.textContent = 'Connected';
@@ -351,12 +1054,56 @@ function connect() {
   eventSource.addEventListener('points', (e) => {
     try {
       const data = JSON.parse(e.data);
-      updateDisplay(data.points, data.metrics);
+      updateDisplay(
+        data.points,
+        data.metrics,
+        { metric: data.progress_metric, value: data.progress_value },
+        data.goal
+      );
+      renderGoalLabel(data.goals_reached, data.goal_count);
+      // Public subscriber counts are rounded, so label them as approximate
+      document.getElementById('subs-label').textContent =
+        !data.is_monitoring || data.using_exact_subscribers ? 'New Subs' : 'New Subs (approx)';
+      renderGoalEta(data.time_to_goal);
+      status.textContent = !data.is_monitoring
+        ? 'Idle'
+        : data.is_paused ? 'Paused' : data.is_stale ? 'Data stale' : 'Connected';
     } catch (err) {
       console.error('Failed to parse event data:', err);
     }
   });
 
+  eventSource.addEventListener('monitoring-paused', (e) => {
+    status.textContent = e.data === 'true' ? 'Paused' : 'Connected';
+  });
+
+  eventSource.addEventListener('cap-reached', () => {
+    if (OVERLAY_OPTIONS.effects) showCelebration();
+    showMessage('MAX!', 5000);
+  });
+
+  // Live tuning from the app; options pinned by this page's query keep their value
+  eventSource.addEventListener('overlay-config', (e) => {
+    try {
+      const data = JSON.parse(e.data);
+      const query = new URLSearchParams(location.search);
+      for (const [key, value] of Object.entries(data)) {
+        if (!query.has(key)) OVERLAY_OPTIONS[key] = value;
+      }
+    } catch (err) {
+      console.error('Failed to parse overlay config:', err);
+    }
+  });
+
+  eventSource.addEventListener('message', (e) => {
+    try {
+      const data = JSON.parse(e.data);
+      showMessage(data.text, data.duration_ms);
+    } catch (err) {
+      console.error('Failed to parse message event:', err);
+    }
+  });
+
   eventSource.onerror = () => {
     status.textContent = 'Disconnected';
     status.className = 'connection-status disconnected';
@@ -365,8 +1112,67 @@ function connect() {
   };
 }
 
+// Stat tile element IDs per source
+const SOURCE_TILES = { superchat: 'superchat', viewers: 'viewers', likes: 'likes', subscribers: 'subs' };
+
+function applySourceVisibility() {
+  for (const [source, id] of Object.entries(SOURCE_TILES)) {
+    if (!OVERLAY_OPTIONS.visible[source]) {
+      document.getElementById(id).closest('.stat-item').style.display = 'none';
+    }
+  }
+}
+
+applySourceVisibility();
+renderProgress();
 connect();
 </script>
 </body>
 </html>
 "##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn total_only_points_event_leaves_sources_to_breakdown() {
+        let payload = PointsPayload {
+            points: PointState {
+                total: 150,
+                superchat: 100,
+                likes: 50,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let full: serde_json::Value =
+            serde_json::from_str(&points_event_data(&payload, false)).unwrap();
+        assert_eq!(full["points"]["superchat"], 100);
+
+        let total_only: serde_json::Value =
+            serde_json::from_str(&points_event_data(&payload, true)).unwrap();
+        assert_eq!(total_only["points"], serde_json::json!({ "total": 150 }));
+        // Everything besides the per-source points is still sent
+        assert_eq!(total_only["goal_points"], full["goal_points"]);
+        assert_eq!(total_only["metrics"], full["metrics"]);
+    }
+
+    #[test]
+    fn overlay_pages_are_own_origin() {
+        assert!(is_overlay_origin("http://localhost:1430", 1430));
+        assert!(is_overlay_origin("http://127.0.0.1:1430", 1430));
+    }
+
+    #[test]
+    fn other_sites_are_not_own_origin() {
+        assert!(!is_overlay_origin("https://example.com", 1430));
+        assert!(!is_overlay_origin("http://localhost:1431", 1430));
+        assert!(!is_overlay_origin(
+            "http://localhost:1430.example.com",
+            1430
+        ));
+        assert!(!is_overlay_origin("null", 1430));
+    }
+}
@@ -13,6 +13,8 @@ interface PointState {
   subscribers: number;
   manual: number;
   visitor: number;
+  membership?: number;
+  bonus?: number;
 }
 
 interface RawMetrics {
@@ -21,6 +23,7 @@ interface RawMetrics {
   like_count: number;
   initial_subscribers: number;
   current_subscribers: number;
+  membership_count?: number;
 }
 
 interface PointsConfig {
@@ -30,12 +33,26 @@ interface PointsConfig {
   subscriber_rate: number;
   manual_rate: number;
   visitor_rate: number;
+  membership_rate?: number;
+  total_cap?: number | null;
+  superchat_cap?: number | null;
+  concurrent_cap?: number | null;
+  like_cap?: number | null;
+  subscriber_cap?: number | null;
 }
 
 interface PointsUpdatePayload {
   points: PointState;
   metrics: RawMetrics;
   config: PointsConfig;
+  is_monitoring: boolean;
+  is_paused?: boolean;
+  using_exact_subscribers?: boolean;
+}
+
+interface LiveInfo {
+  title: string;
+  channelName: string;
 }
 
 function App() {
@@ -45,6 +62,8 @@ function App() {
   const [isLoggedIn, setIsLoggedIn] = useState(false);
   const [serverUrl, setServerUrl] = useState<string | null>(null);
   const [inputShake, setInputShake] = useState(false);
+  const [likesUnavailable, setLikesUnavailable] = useState(false);
+  const [liveInfo, setLiveInfo] = useState<LiveInfo | null>(null);
   const [points, setPoints] = useState<PointState>({
     total: 0,
     superchat: 0,
@@ -88,9 +107,21 @@ function App() {
       }
     });
 
+    const unlistenStreamEnded = listen("stream-ended", () => {
+      setIsMonitoring(false);
+      setLiveInfo(null);
+    });
+
+    // 高評価数が非公開の配信では最後に取得できた値のまま表示する
+    const unlistenLikesUnavailable = listen<string>("likes-unavailable", () => {
+      setLikesUnavailable(true);
+    });
+
     return () => {
       unlistenPoints.then((fn) => fn());
       unlistenCookies.then((fn) => fn());
+      unlistenStreamEnded.then((fn) => fn());
+      unlistenLikesUnavailable.then((fn) => fn());
     };
   }, []);
 
@@ -116,9 +147,12 @@ function App() {
     setIsLoading(true);
     try {
       if (newState) {
+        setLikesUnavailable(false);
         await invoke("start_monitoring", { videoUrl });
+        setLiveInfo(await invoke<LiveInfo | null>("get_stream_info"));
       } else {
         await invoke("stop_monitoring");
+        setLiveInfo(null);
       }
     } catch (e) {
       console.error("Failed to toggle monitoring:", e);
@@ -183,6 +217,15 @@ function App() {
     }
   };
 
+  const logout = async () => {
+    try {
+      await invoke("logout");
+      // Login status will be updated by the youtube-login-status event listener
+    } catch (e) {
+      console.error("Failed to log out:", e);
+    }
+  };
+
   const checkLoginStatus = useCallback(async () => {
     try {
       const cookies = await invoke<string>("get_youtube_cookies");
@@ -231,6 +274,11 @@ function App() {
           </svg>
           <span className="login-status">{isLoggedIn ? "ログイン済" : "ログイン"}</span>
         </button>
+        {isLoggedIn && (
+          <button type="button" className="logout-button" onClick={logout}>
+            ログアウト
+          </button>
+        )}
       </header>
 
       <div className="section">
@@ -264,6 +312,11 @@ function App() {
             </div>
           </div>
         </div>
+        {liveInfo && (
+          <p className="stream-info">
+            監視中: {liveInfo.title} / {liveInfo.channelName}
+          </p>
+        )}
         <div className="button-group">
           <button
             type="button"
@@ -346,8 +399,8 @@ function App() {
                 </div>
               </div>
             </div>
-            <div className="point-item">
-              <span className="label">高評価</span>
+            <div className={`point-item${likesUnavailable ? " unavailable" : ""}`}>
+              <span className="label">高評価{likesUnavailable && "（非公開）"}</span>
               <div className="point-details">
                 <div className="detail-row">
                   <span className="detail-label">値</span>
@@ -420,6 +473,40 @@ function App() {
                 </div>
               </div>
             </div>
+            <div className="point-item">
+              <span className="label">メンバーシップ</span>
+              <div className="point-details">
+                <div className="detail-row">
+                  <span className="detail-label">値</span>
+                  <span className="detail-value">
+                    {(metrics.membership_count ?? 0).toLocaleString()}人
+                  </span>
+                </div>
+                <div className="detail-row">
+                  <span className="detail-label">レート</span>
+                  <span className="detail-value">1人{config.membership_rate ?? 0}円</span>
+                </div>
+                <div className="detail-row">
+                  <span className="detail-label">金額</span>
+                  <span className="detail-value result">
+                    {(points.membership ?? 0).toLocaleString()}円
+                  </span>
+                </div>
+              </div>
+            </div>
+            {(points.bonus ?? 0) > 0 && (
+              <div className="point-item">
+                <span className="label">スパチャ達成ボーナス</span>
+                <div className="point-details">
+                  <div className="detail-row">
+                    <span className="detail-label">金額</span>
+                    <span className="detail-value result">
+                      {(points.bonus ?? 0).toLocaleString()}円
+                    </span>
+                  </div>
+                </div>
+              </div>
+            )}
           </div>
         )}
       </div>
@@ -19,13 +19,26 @@ interface RawMetrics {
   like_count: number;
   initial_subscribers: number;
   current_subscribers: number;
+  extra_metrics?: Record<string, number>;
 }
 
 interface PointsUpdatePayload {
   points: PointState;
   metrics: RawMetrics;
+  is_monitoring: boolean;
+  is_paused?: boolean;
+  using_exact_subscribers?: boolean;
+  goal_points: number;
+  progress_metric?: ProgressMetric;
+  progress_value?: number;
+  goal: number;
+  next_goal?: number | null;
+  goals_reached?: number;
+  goal_count?: number;
 }
 
+type ProgressMetric = "total" | "viewers" | "subscribers" | "superchat";
+
 const TARGET_POINTS = 5000;
 
 function Viewer() {
@@ -46,6 +59,12 @@ function Viewer() {
     current_subscribers: 0,
   });
   const [displayedScore, setDisplayedScore] = useState(0);
+  // Points shown in the total but excluded from goal progress
+  const [goalOffset, setGoalOffset] = useState(0);
+  // Set when the progress bar tracks a metric other than total points
+  const [metricProgress, setMetricProgress] = useState<number | null>(null);
+  const [targetPoints, setTargetPoints] = useState(TARGET_POINTS);
+  const [goalSteps, setGoalSteps] = useState({ reached: 0, count: 1 });
   const [popups, setPopups] = useState<{ id: number; amount: number; left: number }[]>([]);
   const [showSuperEffect, setShowSuperEffect] = useState(false);
   const pointsRef = useRef(points.total);
@@ -108,10 +127,25 @@ function Viewer() {
       setPoints(data);
       setDisplayedScore(data.total);
     });
+    // Start from the configured goal rather than the fallback until the first update arrives
+    invoke<{ overlay?: { goal?: number } }>("get_config").then((config) => {
+      const goal = config?.overlay?.goal;
+      if (goal && goal > 0) setTargetPoints(goal);
+    });
 
     const unlisten = listen<PointsUpdatePayload>("points-update", (event) => {
       handlePointsUpdate(event.payload.points);
       setMetrics(event.payload.metrics);
+      const { points, goal_points, progress_metric, progress_value, goal } = event.payload;
+      setGoalOffset(points.total - (goal_points ?? points.total));
+      setMetricProgress(
+        progress_metric && progress_metric !== "total" ? (progress_value ?? 0) : null,
+      );
+      if (goal > 0) setTargetPoints(goal);
+      setGoalSteps({
+        reached: event.payload.goals_reached ?? 0,
+        count: event.payload.goal_count ?? 1,
+      });
     });
 
     return () => {
@@ -119,7 +153,16 @@ function Viewer() {
     };
   }, [handlePointsUpdate]);
 
-  const progress = Math.min((displayedScore / TARGET_POINTS) * 100, 100);
+  const goalScore = Math.max(metricProgress ?? displayedScore - goalOffset, 0);
+  const progress = Math.min((goalScore / targetPoints) * 100, 100);
+  const goalLabel =
+    goalSteps.count <= 1
+      ? "進捗"
+      : goalSteps.reached >= goalSteps.count
+        ? `目標 ${goalSteps.count} ✓`
+        : goalSteps.reached > 0
+          ? `目標 ${goalSteps.reached} ✓ — 目標 ${goalSteps.reached + 1} へ`
+          : "目標 1";
 
   return (
     <div className="viewer-container">
@@ -133,9 +176,9 @@ function Viewer() {
 
       <div className="progress-section">
         <div className="progress-label">
-          <span>進捗</span>
+          <span>{goalLabel}</span>
           <span>
-            {displayedScore.toLocaleString()} / {TARGET_POINTS.toLocaleString()}
+            {goalScore.toLocaleString()} / {targetPoints.toLocaleString()}
           </span>
         </div>
         <div className="progress-bar">
@@ -170,6 +213,14 @@ function Viewer() {
           <div className="stat-value">{points.visitor.toLocaleString()}</div>
           <div className="stat-label">ライバー訪問</div>
         </div>
+        {Object.entries(metrics.extra_metrics ?? {})
+          .sort(([a], [b]) => a.localeCompare(b))
+          .map(([name, value]) => (
+            <div key={name} className="stat-item">
+              <div className="stat-value">{value.toLocaleString()}</div>
+              <div className="stat-label">{name}</div>
+            </div>
+          ))}
       </div>
 
       {popups.map((popup) => (
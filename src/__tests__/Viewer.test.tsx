@@ -379,6 +379,143 @@ describe("Viewer", () => {
       const progressFill = container.querySelector(".progress-fill");
       expect(progressFill).toHaveStyle({ width: "100%" });
     });
+
+    it("uses goal_points for progress when provided", async () => {
+      mockInvoke.mockResolvedValue({
+        total: 3000,
+        superchat: 1000,
+        concurrent: 0,
+        likes: 0,
+        subscribers: 0,
+        manual: 2000,
+        visitor: 0,
+      });
+      let eventCallback: ((event: { payload: unknown }) => void) | null = null;
+      mockListen.mockImplementation((eventName, callback) => {
+        if (eventName === "points-update") {
+          eventCallback = callback;
+        }
+        return Promise.resolve(() => {});
+      });
+
+      const { container } = await act(async () => {
+        return render(<Viewer />);
+      });
+
+      await act(async () => {
+        eventCallback?.({
+          payload: {
+            points: {
+              total: 3000,
+              superchat: 1000,
+              concurrent: 0,
+              likes: 0,
+              subscribers: 0,
+              manual: 2000,
+              visitor: 0,
+            },
+            metrics: {
+              superchat_amount: 1000,
+              concurrent_viewers: 0,
+              like_count: 0,
+              initial_subscribers: 0,
+              current_subscribers: 0,
+            },
+            goal_points: 1000,
+          },
+        });
+      });
+
+      await act(async () => {
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      });
+
+      const progressFill = container.querySelector(".progress-fill");
+      expect(progressFill).toHaveStyle({ width: "20%" });
+      expect(screen.getByText("1,000 / 5,000")).toBeInTheDocument();
+    });
+
+    it("tracks the selected progress metric against its goal", async () => {
+      mockInvoke.mockResolvedValue({
+        total: 3000,
+        superchat: 1000,
+        concurrent: 0,
+        likes: 0,
+        subscribers: 0,
+        manual: 2000,
+        visitor: 0,
+      });
+      let eventCallback: ((event: { payload: unknown }) => void) | null = null;
+      mockListen.mockImplementation((eventName, callback) => {
+        if (eventName === "points-update") {
+          eventCallback = callback;
+        }
+        return Promise.resolve(() => {});
+      });
+
+      const { container } = await act(async () => {
+        return render(<Viewer />);
+      });
+
+      await act(async () => {
+        eventCallback?.({
+          payload: {
+            points: {
+              total: 3000,
+              superchat: 1000,
+              concurrent: 0,
+              likes: 0,
+              subscribers: 0,
+              manual: 2000,
+              visitor: 0,
+            },
+            metrics: {
+              superchat_amount: 1000,
+              concurrent_viewers: 25,
+              like_count: 0,
+              initial_subscribers: 0,
+              current_subscribers: 0,
+            },
+            goal_points: 3000,
+            progress_metric: "viewers",
+            progress_value: 25,
+            goal: 100,
+          },
+        });
+      });
+
+      await act(async () => {
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      });
+
+      const progressFill = container.querySelector(".progress-fill");
+      expect(progressFill).toHaveStyle({ width: "25%" });
+      expect(screen.getByText("25 / 100")).toBeInTheDocument();
+    });
+
+    it("starts from the configured goal before the first update", async () => {
+      mockInvoke.mockImplementation((command: string) =>
+        Promise.resolve(
+          command === "get_config"
+            ? { overlay: { goal: 3000 } }
+            : {
+                total: 0,
+                superchat: 0,
+                concurrent: 0,
+                likes: 0,
+                subscribers: 0,
+                manual: 0,
+                visitor: 0,
+              },
+        ),
+      );
+
+      await act(async () => {
+        render(<Viewer />);
+      });
+
+      expect(screen.getByText("0 / 3,000")).toBeInTheDocument();
+    });
   });
 
   describe("cleanup", () => {